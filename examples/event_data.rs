@@ -1,4 +1,4 @@
-use discord_presence::{models::EventData, Client, Event};
+use discord_presence::{models::event_types, Client, Event};
 
 mod helpers;
 
@@ -7,11 +7,7 @@ fn main() -> anyhow::Result<()> {
 
     let mut drpc = Client::new(1003450375732482138);
 
-    drpc.on_ready(|ctx| {
-        let EventData::Ready(data) = ctx.event else {
-            unreachable!()
-        };
-
+    drpc.on::<event_types::Ready, _>(|data, _ctx| {
         let _user = data.user;
     })
     .persist();
@@ -1,25 +1,38 @@
 use std::{
+    path::Path,
     sync::{atomic::Ordering, Arc},
     thread::{JoinHandle, Thread},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use crate::{
     connection::Manager as ConnectionManager,
-    event_handler::{Context as EventContext, EventCallbackHandle, HandlerRegistry},
+    event_handler::{
+        Context as EventContext, EventCallbackHandle, HandlerOptions, HandlerPanic, HandlerRegistry,
+    },
+    event_stream::{EventReceiver, EventStreamConfig},
+    executor::HandlerExecutor,
+    interceptor::Interceptor,
     models::{
-        commands::{Subscription, SubscriptionArgs},
-        message::Message,
+        commands::{EmptyArgs, RpcCommand, Subscription, SubscriptionArgs},
+        event_types::RpcEvent,
+        events::{PartialUser, ReadyEvent, RpcServerConfiguration},
         payload::Payload,
         rich_presence::{
             Activity, CloseActivityRequestArgs, SendActivityJoinInviteArgs, SetActivityArgs,
         },
-        Command, Event, OpCode,
+        voice_settings::{UserVoiceSettings, VoiceSettings},
+        Command, Event,
     },
+    session::{Replay, SessionRecorder},
+    state::{ConnectionState, StateWatcher},
     DiscordError, Result,
 };
 use crossbeam_channel::Sender;
-use serde::{de::DeserializeOwned, Serialize};
+use serde::{
+    de::{DeserializeOwned, IgnoredAny},
+    Serialize,
+};
 use serde_json::Value;
 
 macro_rules! event_handler_function {
@@ -41,14 +54,16 @@ macro_rules! event_handler_function {
 
 /// Wrapper around the [`JoinHandle`] returned by [`Client::start`]
 #[allow(clippy::module_name_repetitions)]
-pub struct ClientThread(JoinHandle<()>, Sender<()>);
+pub struct ClientThread(JoinHandle<Result<()>>, Sender<()>);
 
 impl ClientThread {
-    // Ignore missing error docs because it's an alias of `join`
-    #[allow(clippy::missing_errors_doc)]
-    /// Alias of [`JoinHandle::join()`]
-    pub fn join(self) -> std::thread::Result<()> {
-        self.0.join()
+    /// Wait for the client thread to finish, see [`JoinHandle::join()`]
+    ///
+    /// # Errors
+    /// - The thread panicked
+    /// - The client gave up connecting to Discord. The last connection error is returned.
+    pub fn join(self) -> Result<()> {
+        self.0.join().map_err(|_| DiscordError::ThreadError)?
     }
 
     // Ignore missing error docs because it's an alias of `is_finished`
@@ -75,9 +90,7 @@ impl ClientThread {
         // Attempt to send the message to stop the thread
         self.1.send(())?;
 
-        self.join().map_err(|_| DiscordError::EventLoopError)?;
-
-        Ok(())
+        self.join()
     }
 
     /// "Forgets" client thread, removing the variable, but keeping the client running indefinitely.
@@ -123,6 +136,205 @@ impl Client {
         }
     }
 
+    /// Keep waiting for Discord to start, instead of giving up, if it is not running
+    ///
+    /// While waiting, the client retries connecting every `sleep_duration` (see [`Client::with_error_config`]),
+    /// without using up connection attempts or firing [`Event::Error`].
+    ///
+    /// This must be set before calling [`Client::start`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use discord_presence::Client;
+    /// let mut drpc = Client::new(1003450375732482138).with_wait_for_discord(true);
+    /// drpc.start();
+    /// ```
+    #[must_use]
+    pub fn with_wait_for_discord(mut self, wait: bool) -> Self {
+        self.connection_manager.set_wait_for_discord(wait);
+        self
+    }
+
+    /// Replay [`Event::Ready`] and [`Event::Connected`] to handlers registered after they fired
+    ///
+    /// When enabled, a handler registered while the client is ready, or connected,
+    /// is immediately called with the data of the last matching event.
+    /// The state is forgotten once it no longer holds, i.e. both events on disconnect,
+    /// and once the client stops or gives up connecting.
+    /// [`Event::Ready`] only fires once, but after a reconnect it is replayed with the data of the new connection.
+    ///
+    /// The replay is not synchronized with events received while the handler is being registered.
+    /// If the event fires again at that moment, the handler may be called twice,
+    /// and may receive the new data before the replayed data, even with the [`HandlerExecutor::Ordered`] executor.
+    ///
+    /// [`Client::block_until_event`] always behaves this way.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use discord_presence::Client;
+    /// let mut drpc = Client::new(1003450375732482138).with_sticky_events(true);
+    /// drpc.start();
+    ///
+    /// // Fires even if the client became ready before it was registered
+    /// drpc.on_ready(|_ctx| println!("READY!")).persist();
+    /// ```
+    #[must_use]
+    pub fn with_sticky_events(self, sticky: bool) -> Self {
+        self.event_handler_registry.set_replay_sticky(sticky);
+        self
+    }
+
+    /// Choose how event handlers are run, see [`HandlerExecutor`] for the ordering guarantees of each executor
+    ///
+    /// By default, a new thread is spawned for every handler, for every event.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use discord_presence::{executor::HandlerExecutor, Client};
+    /// // Run handlers one at a time, in the order the events were received
+    /// let mut drpc = Client::new(1003450375732482138).with_handler_executor(HandlerExecutor::Ordered);
+    /// drpc.start();
+    /// ```
+    #[must_use]
+    pub fn with_handler_executor(self, executor: HandlerExecutor) -> Self {
+        self.event_handler_registry.set_executor(executor);
+        self
+    }
+
+    /// Subscribe to server-side events automatically when handlers are registered for them, which is the default
+    ///
+    /// Registering the first handler of an event for which [`Event::is_auto_subscribable`] is true sends SUBSCRIBE,
+    /// and dropping the last handle sends UNSUBSCRIBE. The subscriptions are sent again on every new connection.
+    /// Events that need args, such as [`Event::MessageCreate`], must still be subscribed to with [`Client::subscribe`].
+    ///
+    /// Calls to [`Client::subscribe`] and [`Client::unsubscribe`] for these events share the same reference count,
+    /// so a manual subscription is kept when the last handler is dropped, and the other way around.
+    /// This must be set before registering handlers.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use discord_presence::Client;
+    /// let mut drpc = Client::new(1003450375732482138);
+    ///
+    /// // No need to subscribe to ACTIVITY_JOIN
+    /// let _join = drpc.on_activity_join(|ctx| println!("Joining: {:?}", ctx.event));
+    ///
+    /// drpc.start();
+    /// ```
+    #[must_use]
+    pub fn with_auto_subscribe(self, auto_subscribe: bool) -> Self {
+        self.event_handler_registry
+            .set_auto_subscribe(auto_subscribe);
+        self
+    }
+
+    /// Call `handler` when an event handler panics, instead of logging the panic
+    ///
+    /// Panics are always caught, so a panicking handler does not stop other handlers, or the client, from running.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use discord_presence::Client;
+    /// let mut drpc = Client::new(1003450375732482138)
+    ///     .with_panic_handler(|report| {
+    ///         eprintln!(
+    ///             "Handler {} panicked on {:?}: {}",
+    ///             report.handler_id, report.event, report.message
+    ///         );
+    ///     })
+    ///     .with_max_handler_panics(3);
+    /// ```
+    #[must_use]
+    pub fn with_panic_handler<F>(self, handler: F) -> Self
+    where
+        F: Fn(HandlerPanic) + 'static + Send + Sync,
+    {
+        self.event_handler_registry
+            .set_panic_handler(Arc::new(handler));
+        self
+    }
+
+    /// Unregister event handlers once they have panicked `max_panics` times
+    ///
+    /// By default, handlers are never unregistered. The last [`HandlerPanic`] of a handler has `disabled` set.
+    #[must_use]
+    pub fn with_max_handler_panics(self, max_panics: usize) -> Self {
+        self.event_handler_registry
+            .set_max_panics(Some(max_panics.max(1)));
+        self
+    }
+
+    /// Record every frame exchanged with Discord to a JSONL file at `path`, which can later be replayed with [`Client::with_replay`]
+    ///
+    /// Each line is a [`SessionEntry`](crate::session::SessionEntry), with the opcode, the payload, and the time since the recording started.
+    /// Frames are recorded as they are sent and received, so the recording includes the handshake, and ignores interceptors.
+    /// The file is truncated if it exists.
+    ///
+    /// # Errors
+    /// - The file could not be created
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use discord_presence::Client;
+    /// # fn main() -> discord_presence::Result<()> {
+    /// let mut drpc = Client::new(1003450375732482138).with_session_recording("session.jsonl")?;
+    /// drpc.start();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_session_recording<P: AsRef<Path>>(mut self, path: P) -> Result<Self> {
+        self.connection_manager
+            .set_recorder(SessionRecorder::create(path.as_ref())?);
+        Ok(self)
+    }
+
+    /// Replay a recorded session against the event handlers, instead of connecting to Discord
+    ///
+    /// See [`Replay`] for how the recording is replayed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use discord_presence::{session::Replay, Client};
+    /// # use std::time::Duration;
+    /// # fn main() -> discord_presence::Result<()> {
+    /// let replay = Replay::from_file("session.jsonl")?;
+    /// let mut drpc = Client::new(1003450375732482138).with_replay(replay.clone());
+    ///
+    /// drpc.on_activity_join(|ctx| println!("Joining: {:?}", ctx.event))
+    ///     .persist();
+    ///
+    /// drpc.start();
+    /// assert!(replay.wait_until_finished(Duration::from_secs(30)));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn with_replay(mut self, replay: Replay) -> Self {
+        self.connection_manager.set_replay(replay);
+        self
+    }
+
+    /// Add an interceptor, which sees every command before it is sent, and every response and event before it is dispatched
+    ///
+    /// Interceptors run in the order they were added, see [`Interceptor`] for how dropped payloads are handled.
+    /// They are shared by all clones of the client.
+    #[must_use]
+    pub fn with_interceptor<I>(self, interceptor: I) -> Self
+    where
+        I: Interceptor + 'static,
+    {
+        self.connection_manager
+            .add_interceptor(Arc::new(interceptor));
+        self
+    }
+
     // TODO: Add examples
     /// Start the connection manager
     ///
@@ -163,15 +375,14 @@ impl Client {
     ///
     /// # Errors
     /// - The internal connection thread ran into an error
+    /// - The client gave up connecting to Discord. The last connection error is returned.
     /// - The client was not started, or has already been shutdown
     pub fn block_on(mut self) -> Result<()> {
         let thread = self.unwrap_thread()?;
 
         // If into_inner succeeds, await the thread completing.
         // Otherwise, the thread will be dropped and shut down anyway
-        thread.join().map_err(|_| DiscordError::ThreadError)?;
-
-        Ok(())
+        thread.join()
     }
 
     fn unwrap_thread(&mut self) -> Result<ClientThread> {
@@ -190,36 +401,182 @@ impl Client {
         crate::READY.load(Ordering::Relaxed)
     }
 
-    fn execute<A, E>(&mut self, cmd: Command, args: A, evt: Option<Event>) -> Result<Payload<E>>
-    where
-        A: Serialize + Send + Sync,
-        E: Serialize + DeserializeOwned + Send + Sync,
-    {
+    #[must_use]
+    /// Get the data Discord sent when the handshake of the current connection completed
+    ///
+    /// This is kept after the [`Event::Ready`] event has fired,
+    /// so it is available to code that runs after the client is ready.
+    ///
+    /// Returns `None` while the client is not connected, it is cleared when the connection is lost,
+    /// and updated when the client reconnects.
+    pub fn ready_data(&self) -> Option<ReadyEvent> {
+        self.connection_manager.ready_data()
+    }
+
+    #[must_use]
+    /// Get the user that is logged in to Discord
+    ///
+    /// Returns `None` while the client is not connected, see [`Client::ready_data`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use discord_presence::{Client, Event};
+    /// let mut drpc = Client::new(1003450375732482138);
+    /// drpc.start();
+    /// drpc.block_until_event(Event::Ready).unwrap();
+    ///
+    /// if let Some(user) = drpc.current_user() {
+    ///     println!("Discord: connected as {:?}", user.username);
+    /// }
+    /// ```
+    pub fn current_user(&self) -> Option<PartialUser> {
+        self.ready_data().and_then(|ready| ready.user)
+    }
+
+    #[must_use]
+    /// Get the configuration of the RPC server, such as the CDN host and API endpoint
+    ///
+    /// Returns `None` while the client is not connected, see [`Client::ready_data`].
+    pub fn server_config(&self) -> Option<RpcServerConfiguration> {
+        self.ready_data().and_then(|ready| ready.config)
+    }
+
+    #[must_use]
+    /// Get the current state of the connection to Discord
+    pub fn state(&self) -> ConnectionState {
+        self.connection_manager.state().get()
+    }
+
+    #[must_use]
+    /// Watch the state of the connection to Discord for changes
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use discord_presence::{Client, ConnectionState};
+    /// let mut drpc = Client::new(1003450375732482138);
+    /// let mut watcher = drpc.watch_state();
+    ///
+    /// drpc.start();
+    ///
+    /// std::thread::spawn(move || loop {
+    ///     match watcher.changed() {
+    ///         ConnectionState::Ready => println!("Discord: connected"),
+    ///         state if state.is_terminal() => break,
+    ///         state => println!("Discord: {state:?}"),
+    ///     }
+    /// });
+    /// ```
+    pub fn watch_state(&self) -> StateWatcher {
+        StateWatcher::new(self.connection_manager.state().clone())
+    }
+
+    #[must_use]
+    /// Check if the client gave up connecting to Discord
+    ///
+    /// See [`Event::ConnectionFailed`]
+    pub fn is_failed(&self) -> bool {
+        self.connection_manager.has_failed()
+    }
+
+    /// Check that commands can be sent
+    fn check_connected(&self) -> Result<()> {
+        if self.connection_manager.has_failed() {
+            return Err(DiscordError::ConnectionFailed);
+        }
+
         if !crate::READY.load(Ordering::Relaxed) {
             return Err(DiscordError::NotStarted);
         }
 
-        trace!("Executing command: {:?}", cmd);
+        Ok(())
+    }
 
-        let message = Message::new(
-            OpCode::Frame,
-            Payload::with_nonce(cmd, Some(args), None, evt),
-        );
-        self.connection_manager.send(message?)?;
-        let Message { payload, .. } = self.connection_manager.recv()?;
-        let response: Payload<E> = serde_json::from_str(&payload)?;
+    fn execute<A, R, D>(
+        &mut self,
+        cmd: Command,
+        args: A,
+        evt: Option<Event>,
+    ) -> Result<Payload<R, D>>
+    where
+        A: Serialize + Send + Sync,
+        R: DeserializeOwned,
+        D: DeserializeOwned,
+    {
+        self.check_connected()?;
 
-        match response.evt {
-            Some(Event::Error) => Err(DiscordError::SubscriptionFailed),
-            _ => Ok(response),
-        }
+        trace!("Executing command: {cmd:?}");
+
+        let payload: Payload<A> = Payload::with_nonce(cmd, Some(args), None, evt);
+        self.connection_manager.send(payload)?;
+        let response = Payload::try_from(self.connection_manager.recv()?)?;
+
+        response.into_response()
+    }
+
+    /// Send a typed command, and get its response data
+    ///
+    /// Every command supported by this crate has an [`RpcCommand`] in [`models::commands`](crate::models::commands),
+    /// except [`Command::Dispatch`], which is only used by Discord to send events.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use discord_presence::{models::commands::GetVoiceSettings, models::EmptyArgs, Client};
+    /// # let mut drpc = Client::new(1003450375732482138);
+    /// let settings = drpc.send::<GetVoiceSettings>(EmptyArgs::default()).unwrap();
+    /// println!("Muted: {:?}", settings.mute);
+    /// ```
+    ///
+    /// # Errors
+    /// - The args are invalid, see [`RpcCommand::validate`]
+    /// - The response data could not be parsed as [`RpcCommand::Response`]
+    /// - See [`DiscordError`] for more info
+    pub fn send<C>(&mut self, args: C::Args) -> Result<C::Response>
+    where
+        C: RpcCommand,
+    {
+        C::validate(&args)?;
+
+        let evt = C::event(&args);
+        let response: Payload = self.execute(C::command(), args, evt)?;
+
+        Ok(serde_json::from_value(
+            response.data.unwrap_or(Value::Null),
+        )?)
+    }
+
+    /// Send a command that is not natively supported by this crate
+    ///
+    /// The command name and args are sent as-is, and the raw response is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use discord_presence::Client;
+    /// # use serde_json::json;
+    /// # let mut drpc = Client::new(1003450375732482138);
+    /// let response = drpc
+    ///     .send_raw("GET_CHANNEL", json!({ "channel_id": "1234" }), None)
+    ///     .unwrap();
+    /// println!("{:?}", response.data);
+    /// ```
+    ///
+    /// # Errors
+    /// - See [`DiscordError`] for more info
+    pub fn send_raw<C>(&mut self, cmd: C, args: Value, evt: Option<Event>) -> Result<Payload>
+    where
+        C: Into<Command>,
+    {
+        self.execute(cmd.into(), args, evt)
     }
 
     /// Set the users current activity
     ///
     /// # Errors
     /// - See [`DiscordError`] for more info
-    pub fn set_activity<F>(&mut self, f: F) -> Result<Payload<Activity>>
+    pub fn set_activity<F>(&mut self, f: F) -> Result<Payload<SetActivityArgs, Activity>>
     where
         F: FnOnce(Activity) -> Activity,
     {
@@ -230,7 +587,7 @@ impl Client {
     ///
     /// # Errors
     /// - See [`DiscordError`] for more info
-    pub fn clear_activity(&mut self) -> Result<Payload<Activity>> {
+    pub fn clear_activity(&mut self) -> Result<Payload<SetActivityArgs, Activity>> {
         self.execute(Command::SetActivity, SetActivityArgs::default(), None)
     }
 
@@ -241,7 +598,10 @@ impl Client {
     ///
     /// # Errors
     /// - See [`DiscordError`] for more info
-    pub fn send_activity_join_invite(&mut self, user_id: u64) -> Result<Payload<Value>> {
+    pub fn send_activity_join_invite(
+        &mut self,
+        user_id: u64,
+    ) -> Result<Payload<SendActivityJoinInviteArgs, IgnoredAny>> {
         self.execute(
             Command::SendActivityJoinInvite,
             SendActivityJoinInviteArgs::new(user_id),
@@ -253,7 +613,10 @@ impl Client {
     ///
     /// # Errors
     /// - See [`DiscordError`] for more info
-    pub fn close_activity_request(&mut self, user_id: u64) -> Result<Payload<Value>> {
+    pub fn close_activity_request(
+        &mut self,
+        user_id: u64,
+    ) -> Result<Payload<CloseActivityRequestArgs, IgnoredAny>> {
         self.execute(
             Command::CloseActivityRequest,
             CloseActivityRequestArgs::new(user_id),
@@ -261,26 +624,173 @@ impl Client {
         )
     }
 
+    /// Get the user's current voice settings
+    ///
+    /// NOTE: This requires the `rpc.voice.read` scope to be authorized.
+    ///
+    /// # Errors
+    /// - See [`DiscordError`] for more info
+    pub fn get_voice_settings(&mut self) -> Result<Payload<EmptyArgs, VoiceSettings>> {
+        self.execute(Command::GetVoiceSettings, EmptyArgs::default(), None)
+    }
+
+    /// Set the user's voice settings
+    ///
+    /// Only the fields that are set will be changed.
+    ///
+    /// NOTE: This requires the `rpc.voice.write` scope to be authorized.
+    ///
+    /// # Errors
+    /// - A value is outside of the range accepted by Discord
+    /// - See [`DiscordError`] for more info
+    pub fn set_voice_settings<F>(&mut self, f: F) -> Result<Payload<VoiceSettings, VoiceSettings>>
+    where
+        F: FnOnce(VoiceSettings) -> VoiceSettings,
+    {
+        let settings = f(VoiceSettings::new());
+        settings.validate()?;
+
+        self.execute(Command::SetVoiceSettings, settings, None)
+    }
+
+    /// Set the voice settings of another user, such as their volume, pan and mute state
+    ///
+    /// These settings are local to the current user.
+    ///
+    /// NOTE: This requires the `rpc.voice.write` scope to be authorized.
+    ///
+    /// # Errors
+    /// - A value is outside of the range accepted by Discord
+    /// - See [`DiscordError`] for more info
+    pub fn set_user_voice_settings<F>(
+        &mut self,
+        user_id: u64,
+        f: F,
+    ) -> Result<Payload<UserVoiceSettings, UserVoiceSettings>>
+    where
+        F: FnOnce(UserVoiceSettings) -> UserVoiceSettings,
+    {
+        let settings = f(UserVoiceSettings::new()).user_id(user_id.to_string());
+        settings.validate()?;
+
+        self.execute(Command::SetUserVoiceSettings, settings, None)
+    }
+
     /// Subscribe to a given event
     ///
+    /// The args required depend on the event, see the docs of each [`Event`] variant.
+    ///
+    /// Subscriptions to events without args share a reference count with the automatic subscriptions,
+    /// see [`Client::with_auto_subscribe`]. SUBSCRIBE is only sent if the event is not subscribed to yet,
+    /// and the subscription is kept until it is released by every handler and [`Client::unsubscribe`] call.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use discord_presence::{models::SubscriptionArgs, Client, Event};
+    /// # let mut drpc = Client::new(1003450375732482138);
+    /// drpc.subscribe(Event::ActivityJoin, SubscriptionArgs::default())
+    ///     .unwrap();
+    ///
+    /// drpc.subscribe(Event::SpeakingStart, SubscriptionArgs::channel(1234))
+    ///     .unwrap();
+    /// ```
+    ///
     /// # Errors
     /// - See [`DiscordError`] for more info
-    pub fn subscribe<F>(&mut self, evt: Event, f: F) -> Result<Payload<Subscription>>
+    pub fn subscribe<A>(
+        &mut self,
+        evt: Event,
+        args: A,
+    ) -> Result<Payload<SubscriptionArgs, Subscription>>
     where
-        F: FnOnce(SubscriptionArgs) -> SubscriptionArgs,
+        A: Into<SubscriptionArgs>,
     {
-        self.execute(Command::Subscribe, f(SubscriptionArgs::new()), Some(evt))
+        self.update_subscription(Command::Subscribe, evt, args.into())
     }
 
     /// Unsubscribe from a given event
     ///
+    /// The args must match those that were used to subscribe to the event.
+    /// For events without args, UNSUBSCRIBE is only sent once no handler or other [`Client::subscribe`] call
+    /// holds the subscription anymore, see [`Client::subscribe`].
+    ///
     /// # Errors
     /// - See [`DiscordError`] for more info
-    pub fn unsubscribe<F>(&mut self, evt: Event, f: F) -> Result<Payload<Subscription>>
+    pub fn unsubscribe<A>(
+        &mut self,
+        evt: Event,
+        args: A,
+    ) -> Result<Payload<SubscriptionArgs, Subscription>>
     where
-        F: FnOnce(SubscriptionArgs) -> SubscriptionArgs,
+        A: Into<SubscriptionArgs>,
     {
-        self.execute(Command::Unsubscribe, f(SubscriptionArgs::new()), Some(evt))
+        self.update_subscription(Command::Unsubscribe, evt, args.into())
+    }
+
+    /// Sends SUBSCRIBE or UNSUBSCRIBE, counting subscriptions to events without args like automatic subscriptions
+    fn update_subscription(
+        &mut self,
+        cmd: Command,
+        evt: Event,
+        args: SubscriptionArgs,
+    ) -> Result<Payload<SubscriptionArgs, Subscription>> {
+        if !evt.is_auto_subscribable() || args != SubscriptionArgs::default() {
+            return self.execute(cmd, args, Some(evt));
+        }
+
+        self.check_connected()?;
+
+        let registry = self.event_handler_registry.clone();
+        let subscribe = cmd == Command::Subscribe;
+        let counted = if subscribe {
+            Some(registry.acquire_subscription(&evt))
+        } else {
+            registry.release_subscription(&evt)
+        };
+
+        if counted == Some(false) {
+            // Still held by handlers or other subscriptions, so Discord already has the wanted state
+            let name = serde_json::to_value(&evt)?;
+            let data = Subscription::new().evt(name.as_str().unwrap_or_default());
+
+            return Ok(Payload::with_nonce(cmd, Some(args), Some(data), Some(evt)));
+        }
+
+        let result = self.execute(cmd, args, Some(evt.clone()));
+
+        // Undo the count, which also queues the opposite command in case a pending change was dropped
+        if result.is_err() && counted.is_some() {
+            if subscribe {
+                registry.remove_subscription(&evt);
+            } else {
+                registry.add_subscription(&evt);
+            }
+        }
+
+        result
+    }
+
+    /// Subscribe to the [`Event::GuildStatus`] event of the given guild
+    ///
+    /// # Errors
+    /// - See [`DiscordError`] for more info
+    pub fn subscribe_guild_status(
+        &mut self,
+        guild_id: u64,
+    ) -> Result<Payload<SubscriptionArgs, Subscription>> {
+        self.subscribe(Event::GuildStatus, SubscriptionArgs::guild(guild_id))
+    }
+
+    /// Unsubscribe from the [`Event::GuildStatus`] event of the given guild
+    ///
+    /// # Errors
+    /// - See [`DiscordError`] for more info
+    pub fn unsubscribe_guild_status(
+        &mut self,
+        guild_id: u64,
+    ) -> Result<Payload<SubscriptionArgs, Subscription>> {
+        self.unsubscribe(Event::GuildStatus, SubscriptionArgs::guild(guild_id))
     }
 
     /// Listens for a given event, and returns a handle that unregisters the listener when it is dropped.
@@ -350,11 +860,185 @@ impl Client {
         self.event_handler_registry.register(event, handler)
     }
 
+    /// Listens for a given event, with a priority, or only once, see [`HandlerOptions`]
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use discord_presence::{event_handler::HandlerOptions, Client, Event};
+    /// let mut drpc = Client::new(1003450375732482138);
+    ///
+    /// // Claim join requests while the join dialog is open, so other handlers don't see them
+    /// let _dialog = drpc.on_event_with(
+    ///     Event::ActivityJoinRequest,
+    ///     HandlerOptions::new().priority(10),
+    ///     |ctx| {
+    ///         println!("Showing the join dialog");
+    ///         ctx.consume();
+    ///     },
+    /// );
+    ///
+    /// let _fallback = drpc.on_activity_join_request(|_ctx| {
+    ///     println!("Not called while the dialog handler is registered");
+    /// });
+    /// ```
+    pub fn on_event_with<F>(
+        &self,
+        event: Event,
+        options: HandlerOptions,
+        handler: F,
+    ) -> EventCallbackHandle
+    where
+        F: Fn(EventContext) + 'static + Send + Sync,
+    {
+        self.event_handler_registry
+            .register_with(event, handler, options)
+    }
+
+    /// Listens for the next occurrence of a given event, after which the handler is unregistered
+    ///
+    /// The handler is still unregistered if the returned handle is dropped before the event is fired.
+    pub fn once<F>(&self, event: Event, handler: F) -> EventCallbackHandle
+    where
+        F: Fn(EventContext) + 'static + Send + Sync,
+    {
+        self.on_event_with(event, HandlerOptions::new().once(), handler)
+    }
+
+    /// Listens for a given event, passing its data to the handler already destructured
+    ///
+    /// The event and the type of its data are determined by the [`RpcEvent`], see [`models::event_types`](crate::models::event_types).
+    /// Events with data that does not match the event, e.g. because it failed to parse, are logged and skipped.
+    ///
+    /// This registers a regular handler, so it behaves like [`Client::on_event`] in every other way.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use discord_presence::{models::event_types, Client};
+    /// let mut drpc = Client::new(1003450375732482138);
+    ///
+    /// let _ready = drpc.on::<event_types::Ready, _>(|ready, _ctx| {
+    ///     println!("Logged in as {:?}", ready.user);
+    /// });
+    ///
+    /// let _join = drpc.on::<event_types::ActivityJoin, _>(|join, _ctx| {
+    ///     println!("Joining with secret {:?}", join.secret);
+    /// });
+    ///
+    /// drpc.start();
+    /// drpc.block_on().unwrap();
+    /// ```
+    pub fn on<E, F>(&self, handler: F) -> EventCallbackHandle
+    where
+        E: RpcEvent,
+        F: Fn(E::Data, EventContext) + 'static + Send + Sync,
+    {
+        self.on_event(E::event(), move |ctx| match E::data(ctx.event.clone()) {
+            Some(data) => handler(data, ctx),
+            None => error!(
+                "Skipping {:?} handler, received unexpected data: {:?}",
+                E::event(),
+                ctx.event
+            ),
+        })
+    }
+
+    /// Receive every event through a channel, rather than a callback
+    ///
+    /// Handlers registered with [`Client::on_event`] are still called as usual.
+    /// The receiver is unbounded, see [`Client::events_with`] to filter events or limit the number of queued events.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use discord_presence::Client;
+    /// let mut drpc = Client::new(1003450375732482138);
+    /// let events = drpc.events();
+    /// drpc.start();
+    ///
+    /// for (event, data) in events {
+    ///     println!("{event:?}: {data:?}");
+    /// }
+    /// ```
+    #[must_use]
+    pub fn events(&self) -> EventReceiver {
+        self.events_with(EventStreamConfig::new())
+    }
+
+    /// Receive the events selected by `config` through a channel, rather than a callback
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use discord_presence::{event_stream::{EventStreamConfig, OverflowPolicy}, Client, Event};
+    /// let mut drpc = Client::new(1003450375732482138);
+    /// let events = drpc.events_with(
+    ///     EventStreamConfig::new()
+    ///         .event(Event::ActivityJoin)
+    ///         .capacity(16, OverflowPolicy::DropOldest),
+    /// );
+    /// drpc.start();
+    ///
+    /// // In the app's main loop
+    /// for (event, data) in events.try_iter() {
+    ///     println!("{event:?}: {data:?}");
+    /// }
+    /// ```
+    #[must_use]
+    pub fn events_with(&self, config: EventStreamConfig) -> EventReceiver {
+        EventReceiver::new(&self.event_handler_registry, config)
+    }
+
+    /// Listens for every event, including events that are not natively supported, and events added in later versions
+    ///
+    /// Wildcard handlers are handed to the executor after the handlers of the event itself, and are not affected by priorities,
+    /// so they also receive events that were [consumed](EventContext::consume).
+    /// They only run after the handlers of the event with the [`HandlerExecutor::Inline`] and [`HandlerExecutor::Ordered`] executors,
+    /// other executors may run them concurrently, in any order.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use discord_presence::Client;
+    /// let mut drpc = Client::new(1003450375732482138);
+    ///
+    /// drpc.on_any(|event, ctx| {
+    ///     println!("{event:?}: {:?}", ctx.event);
+    /// })
+    /// .persist();
+    /// ```
+    pub fn on_any<F>(&self, handler: F) -> EventCallbackHandle
+    where
+        F: Fn(Event, EventContext) + 'static + Send + Sync,
+    {
+        self.event_handler_registry.register_any(handler)
+    }
+
+    /// Listens for an event by its name, including events that are not natively supported by this crate
+    ///
+    /// Events that are not natively supported are received as [`Event::Other`],
+    /// with their data as [`EventData::Unknown`](crate::models::EventData::Unknown).
+    ///
+    /// Remember to subscribe to the event, using [`Client::subscribe`] or [`Client::send_raw`].
+    pub fn on_raw_event<S, F>(&self, name: S, handler: F) -> EventCallbackHandle
+    where
+        S: Into<String>,
+        F: Fn(EventContext) + 'static + Send + Sync,
+    {
+        self.on_event(Event::from(name.into()), handler)
+    }
+
     /// Block the current thread until the event is fired
     ///
     /// Returns the context the event was fired in
     ///
     /// NOTE: Please only use this for the ready event, or if you know what you are doing.
+    /// This blocks forever if the event is never fired, see [`Client::wait_for`] for a variant with a timeout.
+    ///
+    /// If `event` is [`Event::Ready`] or [`Event::Connected`] and the client is already ready, or connected,
+    /// this returns immediately, see [`Client::with_sticky_events`].
     ///
     /// # Errors
     /// - Channel disconnected
@@ -373,7 +1057,7 @@ impl Client {
         };
 
         // `handler` is automatically unregistered once this variable drops
-        let cb_handle = self.on_event(event, handler);
+        let cb_handle = self.event_handler_registry.register_sticky(event, handler);
 
         let response = rx.recv()?;
 
@@ -382,6 +1066,113 @@ impl Client {
         Ok(response)
     }
 
+    /// Block the current thread until `event` is fired with a context matching `predicate`, or the timeout elapses
+    ///
+    /// Events that don't match the predicate are skipped.
+    /// If `event` is [`Event::Ready`] or [`Event::Connected`] and the client is already ready, or connected,
+    /// the current state is matched immediately, see [`Client::with_sticky_events`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use discord_presence::{Client, Event};
+    /// let mut drpc = Client::new(1003450375732482138);
+    /// drpc.start();
+    ///
+    /// drpc.wait_for(Event::Ready, Duration::from_secs(10), |_ctx| true)
+    ///     .expect("Discord did not get ready in time");
+    /// ```
+    ///
+    /// # Errors
+    /// - [`DiscordError::EventTimeout`] if no matching event was fired in time
+    /// - [`DiscordError::ConnectionFailed`] if the client gave up connecting to Discord, as the event would never be fired
+    pub fn wait_for<P>(
+        &self,
+        event: Event,
+        timeout: Duration,
+        mut predicate: P,
+    ) -> Result<EventContext>
+    where
+        P: FnMut(&EventContext) -> bool,
+    {
+        self.wait_for_any([event], timeout, |_, ctx| predicate(ctx))
+            .map(|(_, ctx)| ctx)
+    }
+
+    /// Block the current thread until any of `events` is fired with a context matching `predicate`, or the timeout elapses
+    ///
+    /// Returns the event that was fired, along with its context. See [`Client::wait_for`] for more info.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use discord_presence::{Client, Event};
+    /// let mut drpc = Client::new(1003450375732482138);
+    /// drpc.start();
+    ///
+    /// let (event, ctx) = drpc
+    ///     .wait_for_any([Event::Ready, Event::Error], Duration::from_secs(10), |_, _| true)
+    ///     .unwrap();
+    ///
+    /// if event == Event::Error {
+    ///     println!("Failed to connect: {:?}", ctx.event);
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// - [`DiscordError::EventTimeout`] if no matching event was fired in time
+    /// - [`DiscordError::ConnectionFailed`] if the client gave up connecting to Discord, as the events would never be fired
+    pub fn wait_for_any<I, P>(
+        &self,
+        events: I,
+        timeout: Duration,
+        mut predicate: P,
+    ) -> Result<(Event, EventContext)>
+    where
+        I: IntoIterator<Item = Event>,
+        P: FnMut(&Event, &EventContext) -> bool,
+    {
+        let deadline = Instant::now() + timeout;
+        let events: Vec<Event> = events.into_iter().collect();
+        let (tx, rx) = crossbeam_channel::unbounded::<(Event, EventContext)>();
+
+        // Also listen for the client giving up, so that callers don't wait for events that will never be fired.
+        // The handlers are automatically unregistered once they drop.
+        let _handles: Vec<EventCallbackHandle> = events
+            .iter()
+            .chain([&Event::ConnectionFailed])
+            .map(|event| {
+                let tx = tx.clone();
+                let fired = event.clone();
+                self.event_handler_registry
+                    .register_sticky(event.clone(), move |ctx| {
+                        let _ = tx.send((fired.clone(), ctx));
+                    })
+            })
+            .collect();
+
+        // Checked after registering, so that a failure in between is not missed
+        if self.is_failed() {
+            return Err(DiscordError::ConnectionFailed);
+        }
+
+        loop {
+            let Ok((event, ctx)) = rx.recv_deadline(deadline) else {
+                return Err(DiscordError::EventTimeout { events, timeout });
+            };
+
+            if events.contains(&event) && predicate(&event, &ctx) {
+                return Ok((event, ctx));
+            }
+
+            if event == Event::ConnectionFailed {
+                return Err(DiscordError::ConnectionFailed);
+            }
+        }
+    }
+
     event_handler_function!(on_ready, Event::Ready);
 
     event_handler_function!(on_error, Event::Error);
@@ -395,6 +1186,10 @@ impl Client {
     event_handler_function!(on_connected, Event::Connected);
 
     event_handler_function!(on_disconnected, Event::Disconnected);
+
+    event_handler_function!(on_connection_attempt, Event::ConnectionAttempt);
+
+    event_handler_function!(on_connection_failed, Event::ConnectionFailed);
 }
 
 #[cfg(test)]
@@ -27,6 +27,17 @@ macro_rules! try_until_done {
     }
 }
 
+/// The first message sent on a new connection
+pub(crate) fn handshake_message(client_id: u64) -> Result<Message> {
+    let hs = json![{
+        "client_id": client_id.to_string(),
+        "v": 1,
+        "nonce": utils::nonce()
+    }];
+
+    Message::new(OpCode::Handshake, hs)
+}
+
 pub trait Connection: Sized {
     type Socket: Write + Read;
 
@@ -59,24 +70,9 @@ pub trait Connection: Sized {
         }
     }
 
-    /// Perform a handshake on this socket connection.
-    /// Will block until complete.
-    fn handshake(&mut self, client_id: u64) -> Result<Message> {
-        let hs = json![{
-            "client_id": client_id.to_string(),
-            "v": 1,
-            "nonce": utils::nonce()
-        }];
-
-        let msg = Message::new(OpCode::Handshake, hs)?;
-        try_until_done!(self.send(&msg));
-        let msg = try_until_done!(self.recv());
-
-        Ok(msg)
-    }
-
     /// Ping the server and get a pong response.
     /// Will block until complete.
+    #[allow(dead_code)]
     fn ping(&mut self) -> Result<OpCode> {
         let message = Message::new(OpCode::Ping, json![{}])?;
         try_until_done!(self.send(&message));
@@ -87,13 +83,13 @@ pub trait Connection: Sized {
     /// Send a message to the server.
     fn send(&mut self, message: &Message) -> Result<()> {
         match message.encode() {
-            Err(why) => error!("{:?}", why),
+            Err(why) => error!("{why:?}"),
             Ok(bytes) => {
                 assert!(bytes.len() <= MAX_RPC_FRAME_SIZE);
                 self.socket().write_all(&bytes)?;
             }
-        };
-        trace!("-> {:?}", message);
+        }
+        trace!("-> {message:?}");
         Ok(())
     }
 
@@ -105,7 +101,7 @@ pub trait Connection: Sized {
 
         trace!("Reading header");
         let n = self.socket().read(&mut buf)?;
-        trace!("Received {} bytes for header", n);
+        trace!("Received {n} bytes for header");
 
         if n == 0 {
             return Err(DiscordError::ConnectionClosed);
@@ -123,7 +119,7 @@ pub trait Connection: Sized {
 
         trace!("Reading payload");
         let n = self.socket().read(&mut message_buf)?;
-        trace!("Received {} bytes for payload", n);
+        trace!("Received {n} bytes for payload");
 
         if n == 0 {
             return Err(DiscordError::NoMessage);
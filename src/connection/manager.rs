@@ -1,34 +1,53 @@
-use super::{Connection, Socket};
+use super::Transport;
 use crate::models::EventData;
 use crate::{
     error::{DiscordError, Result},
-    event_handler::HandlerRegistry,
-    models::{payload::Payload, ErrorEvent, Event, Message},
+    event_handler::{Frame, HandlerRegistry},
+    interceptor::{Action, Interceptor, InterceptorChain},
+    models::{
+        payload::Payload, Command, ConnectionAttemptEvent, ConnectionFailedEvent, ErrorEvent,
+        Event, Message, OpCode, ReadyEvent, SubscriptionArgs,
+    },
+    session::{Replay, SessionRecorder},
+    state::{ConnectionState, SharedState},
 };
 use crossbeam_channel::{unbounded, Receiver, Sender};
-use parking_lot::Mutex;
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
 use serde_json::Value as JsonValue;
 use std::{
+    collections::HashSet,
     io::ErrorKind,
     sync::{atomic::Ordering, Arc},
     thread,
-    time::{self, Duration},
+    time::{self, Duration, SystemTime},
 };
 
 type Tx = Sender<Message>;
 type Rx = Receiver<Message>;
+// Command responses, or the reason there is no response
+type ResponseTx = Sender<Result<Message>>;
+type ResponseRx = Receiver<Result<Message>>;
 
 // TODO: Refactor connection manager
 #[derive(Clone)]
 pub struct Manager {
-    connection: Arc<Option<Mutex<Socket>>>,
+    connection: Arc<Option<Mutex<Transport>>>,
     client_id: u64,
     outbound: (Rx, Tx),
-    inbound: (Rx, Tx),
+    inbound: (ResponseRx, ResponseTx),
     handshake_completed: bool,
     event_handler_registry: Arc<HandlerRegistry>,
     error_sleep: Duration,
     connection_attempts: Arc<Mutex<Option<usize>>>,
+    wait_for_discord: bool,
+    state: Arc<SharedState>,
+    ready: Arc<RwLock<Option<ReadyEvent>>>,
+    /// Nonces of the commands sent by the manager itself, whose responses are not returned to the client
+    internal_nonces: Arc<Mutex<HashSet<String>>>,
+    interceptors: Arc<InterceptorChain>,
+    recorder: Option<Arc<SessionRecorder>>,
+    replay: Option<Replay>,
 }
 
 impl Manager {
@@ -51,107 +70,271 @@ impl Manager {
             event_handler_registry,
             error_sleep,
             connection_attempts: Arc::new(Mutex::new(connection_attempts)),
+            wait_for_discord: false,
+            state: Arc::new(SharedState::default()),
+            ready: Arc::new(RwLock::new(None)),
+            internal_nonces: Arc::new(Mutex::new(HashSet::new())),
+            interceptors: Arc::new(InterceptorChain::default()),
+            recorder: None,
+            replay: None,
         }
     }
 
-    pub fn start(&mut self, rx: Receiver<()>) -> std::thread::JoinHandle<()> {
+    pub fn set_wait_for_discord(&mut self, wait: bool) {
+        self.wait_for_discord = wait;
+    }
+
+    pub fn set_recorder(&mut self, recorder: SessionRecorder) {
+        self.recorder = Some(Arc::new(recorder));
+    }
+
+    pub fn set_replay(&mut self, replay: Replay) {
+        self.replay = Some(replay);
+    }
+
+    pub fn start(&mut self, rx: Receiver<()>) -> std::thread::JoinHandle<Result<()>> {
         let mut manager_inner = self.clone();
         let error_sleep = self.error_sleep;
         let connection_attempts = self.connection_attempts.clone();
+        self.state.set(ConnectionState::Connecting);
         thread::spawn(move || {
             // TODO: Refactor so that JSON values are consistent across errors
-            send_and_receive_loop(&mut manager_inner, &rx, error_sleep, &connection_attempts);
+            let result =
+                send_and_receive_loop(&mut manager_inner, &rx, error_sleep, &connection_attempts);
+
+            // A failed loop has already finished, before it handled the ConnectionFailed event
+            if result.is_ok() {
+                manager_inner.finish(ConnectionState::Closed);
+            }
+
+            result
         })
     }
 
-    pub fn send(&self, message: Message) -> Result<()> {
-        self.outbound.1.send(message)?;
+    /// Stops for good, in either the `Closed` or the `Failed` state
+    fn finish(&self, state: ConnectionState) {
+        // The client is neither ready nor connected anymore, don't replay those events to late handlers
+        self.event_handler_registry.clear_sticky(&Event::Ready);
+        self.event_handler_registry.clear_sticky(&Event::Connected);
+        *self.ready.write() = None;
+
+        if state == ConnectionState::Failed {
+            crate::READY.store(false, Ordering::Relaxed);
+        }
+
+        self.state.set(state);
+    }
+
+    pub fn state(&self) -> &Arc<SharedState> {
+        &self.state
+    }
+
+    /// The data of the READY event of the current connection, received when the handshake completed
+    pub fn ready_data(&self) -> Option<ReadyEvent> {
+        self.ready.read().clone()
+    }
+
+    /// Whether the connection manager gave up connecting to Discord
+    pub fn has_failed(&self) -> bool {
+        self.state.get() == ConnectionState::Failed
+    }
+
+    pub fn add_interceptor(&self, interceptor: Arc<dyn Interceptor>) {
+        self.interceptors.add(interceptor);
+    }
+
+    /// Runs the outbound interceptors, then encodes and sends the payload
+    ///
+    /// Fails with [`DiscordError::Intercepted`] if an interceptor dropped the payload.
+    pub fn send<A, D>(&self, payload: Payload<A, D>) -> Result<()>
+    where
+        A: Serialize,
+        D: Serialize,
+    {
+        let mut payload = payload.into_value()?;
+
+        if self.interceptors.outbound(&mut payload) == Action::Drop {
+            trace!("Outbound {:?} dropped by an interceptor", payload.cmd);
+            return Err(DiscordError::Intercepted {
+                command: payload.cmd,
+            });
+        }
+
+        self.outbound
+            .1
+            .send(Message::new(OpCode::Frame, payload)?)?;
 
         Ok(())
     }
 
     pub fn recv(&self) -> Result<Message> {
-        self.inbound.0.recv().map_err(DiscordError::from)
+        self.inbound.0.recv()?
     }
 
-    fn connect(&mut self) -> Result<()> {
+    /// Sends a SUBSCRIBE or UNSUBSCRIBE command on behalf of the event handlers
+    fn send_subscription(&self, cmd: Command, event: Event) -> Result<()> {
+        trace!("Automatic {cmd:?} for {event:?}");
+
+        let payload = Payload::<SubscriptionArgs>::with_nonce(
+            cmd,
+            Some(SubscriptionArgs::default()),
+            None,
+            Some(event),
+        );
+
+        let nonce = payload.nonce.clone().unwrap_or_default();
+        self.internal_nonces.lock().insert(nonce.clone());
+
+        let result = self.send(payload);
+        if result.is_err() {
+            self.internal_nonces.lock().remove(&nonce);
+        }
+
+        result
+    }
+
+    /// Sends the subscriptions of the event handlers that changed since they were last sent
+    fn flush_subscriptions(&self) -> Result<()> {
+        for (cmd, event) in self.event_handler_registry.take_pending_subscriptions() {
+            self.send_subscription(cmd, event)?;
+        }
+
+        Ok(())
+    }
+
+    /// Connect and perform the handshake, publishing `attempt_state` while connecting
+    fn connect(&mut self, attempt_state: ConnectionState) -> Result<()> {
         if self.connection.is_some() {
             return Ok(());
         }
 
+        // Waiting for Discord to start is not a connection attempt
+        if !(self.wait_for_discord && self.state.get() == ConnectionState::Discovering) {
+            self.state.set(attempt_state);
+        }
+
         trace!("Connecting");
 
-        let mut new_connection = Socket::connect()?;
+        let mut new_connection = Transport::connect(self.replay.as_ref(), self.recorder.clone())?;
+
+        self.state.set(ConnectionState::Handshaking);
 
         trace!("Performing handshake");
         let msg = new_connection.handshake(self.client_id)?;
-        let payload: Payload<JsonValue> = serde_json::from_str(&msg.payload)?;
 
-        // TODO: Ensure it works without clone
-        // Only handle the ready event if the client was not already ready
-        if !crate::READY.load(std::sync::atomic::Ordering::Relaxed) {
-            trace!("Discord client is ready!");
-            crate::READY.store(true, Ordering::Relaxed);
+        if msg.opcode == OpCode::Close {
+            let error: ErrorEvent = serde_json::from_str(&msg.payload)?;
 
-            self.event_handler_registry.handle(
-                Event::Ready,
-                Event::Ready.parse_data(into_error!(payload.data)?),
-            );
+            return Err(DiscordError::ConnectionRejected {
+                code: error.code.unwrap_or_default(),
+                message: error.message.unwrap_or_default(),
+            });
         }
 
-        self.event_handler_registry
-            .handle(Event::Connected, EventData::None);
+        let received_at = SystemTime::now();
+        let raw: JsonValue = serde_json::from_str(&msg.payload)?;
+        let payload: Payload<JsonValue> = serde_json::from_value(raw.clone())?;
+        let frame = Frame {
+            raw,
+            nonce: payload.nonce,
+            cmd: payload.cmd,
+            received_at,
+        };
+
+        let ready_data = Event::Ready.parse_data(into_error!(payload.data)?);
+
+        self.event_handler_registry.next_generation();
+
+        // Keep the latest ready data, the user may have changed since the last connection
+        if let EventData::Ready(ref ready) = ready_data {
+            *self.ready.write() = Some(ready.clone());
+        }
 
         trace!("Handshake completed");
 
         self.connection = Arc::new(Some(Mutex::new(new_connection)));
 
+        // A new connection has no subscriptions yet
+        self.internal_nonces.lock().clear();
+        for event in self.event_handler_registry.active_subscriptions() {
+            self.send_subscription(Command::Subscribe, event)?;
+        }
+
+        // Set before the events are handled, so that their handlers and waiters see the client as ready
+        self.state.set(ConnectionState::Ready);
+
+        // Only handle the ready event if the client was not already ready
+        if crate::READY.load(std::sync::atomic::Ordering::Relaxed) {
+            // Ready was handled before the connection was lost, late handlers still get the new data
+            self.event_handler_registry
+                .set_sticky(&Event::Ready, ready_data, Some(frame));
+        } else {
+            trace!("Discord client is ready!");
+            crate::READY.store(true, Ordering::Relaxed);
+
+            self.event_handler_registry
+                .handle_frame(&Event::Ready, ready_data, Some(frame));
+        }
+
+        self.event_handler_registry
+            .handle(&Event::Connected, EventData::None);
+
         trace!("Connected");
 
         Ok(())
     }
 
     fn disconnect(&mut self) {
+        if let Some(ref recorder) = self.recorder {
+            recorder.closed();
+        }
+
         self.handshake_completed = false;
         self.connection = Arc::new(None);
+        self.state.set(ConnectionState::Reconnecting);
+
+        // The READY data is only valid for the connection it was received on
+        *self.ready.write() = None;
+        self.event_handler_registry.clear_sticky(&Event::Ready);
+        self.event_handler_registry.clear_sticky(&Event::Connected);
+        self.event_handler_registry
+            .handle(&Event::Disconnected, EventData::None);
     }
 }
 
+/// Runs until the client is stopped, or returns the last connection error once the manager gives up connecting
 fn send_and_receive_loop(
     manager: &mut Manager,
     rx: &Receiver<()>,
     err_sleep: Duration,
     connection_attempts: &Arc<Mutex<Option<usize>>>,
-) {
+) -> Result<()> {
     trace!("Starting sender loop");
 
-    let mut inbound = manager.inbound.1.clone();
-    let outbound = manager.outbound.0.clone();
+    // Number of failed attempts since the last successful connection
+    let mut attempt = 0;
+    let mut connected_once = false;
 
     loop {
         if rx.try_recv().is_ok() {
-            break;
+            return Ok(());
         }
 
         let connection = manager.connection.clone();
 
         match *connection {
             Some(ref conn) => {
-                match send_and_receive(
-                    &mut conn.lock(),
-                    &manager.event_handler_registry,
-                    &mut inbound,
-                    &outbound,
-                ) {
+                if let Err(err) = manager.flush_subscriptions() {
+                    error!("Failed to update subscriptions: {err}");
+                }
+
+                match send_and_receive(&mut conn.lock(), manager) {
                     Err(DiscordError::IoError(ref err)) if err.kind() == ErrorKind::WouldBlock => {}
                     Err(DiscordError::IoError(_) | DiscordError::ConnectionClosed) => {
                         manager.disconnect();
-                        manager
-                            .event_handler_registry
-                            .handle(Event::Disconnected, EventData::None);
                     }
                     Err(DiscordError::TimeoutError(_)) => continue,
-                    Err(why) => trace!("discord error: {}", why),
+                    Err(why) => trace!("discord error: {why}"),
                     _ => {}
                 }
 
@@ -159,44 +342,102 @@ fn send_and_receive_loop(
 
                 thread::sleep(time::Duration::from_millis(500));
             }
-            None => match manager.connect() {
+            None => match manager.connect(attempt_state(connected_once)) {
+                Err(err) if err.is_not_running() && manager.wait_for_discord => {
+                    // Keep watching for Discord to start, without using up connection attempts
+                    trace!("Discord is not running, waiting for it to start: {err}");
+                    manager.state.set(ConnectionState::Discovering);
+
+                    thread::sleep(err_sleep);
+                }
                 Err(err) => {
+                    if err.is_not_running() {
+                        manager.state.set(ConnectionState::Discovering);
+                    } else if manager.state.get() == ConnectionState::Handshaking {
+                        manager.state.set(attempt_state(connected_once));
+                    }
+
                     manager.event_handler_registry.handle(
-                        Event::Error,
+                        &Event::Error,
                         crate::models::EventData::Error(ErrorEvent {
                             code: None,
                             message: Some(err.to_string()),
                         }),
                     );
 
-                    if err.should_break() {
-                        break;
-                    }
-                    error!("Failed to connect: {:?}", err);
-
-                    let mut attempts = connection_attempts.lock();
-                    if let Some(ref mut attempts) = *attempts {
-                        if *attempts == 0 {
-                            break;
+                    attempt += 1;
+
+                    let exhausted = {
+                        let mut attempts = connection_attempts.lock();
+                        match *attempts {
+                            Some(0) => true,
+                            Some(ref mut attempts) => {
+                                *attempts -= 1;
+                                false
+                            }
+                            None => false,
                         }
-
-                        *attempts -= 1;
+                    };
+
+                    if exhausted || err.is_fatal() {
+                        error!("Failed to connect, not retrying: {err:?}");
+
+                        // Failed before the event is handled, so that handlers and waiters see it
+                        manager.finish(ConnectionState::Failed);
+                        manager.event_handler_registry.handle(
+                            &Event::ConnectionFailed,
+                            EventData::ConnectionFailed(
+                                ConnectionFailedEvent::new()
+                                    .attempts(attempt)
+                                    .error(err.to_string()),
+                            ),
+                        );
+
+                        return Err(err);
                     }
+                    error!("Failed to connect: {err:?}");
+
+                    manager.event_handler_registry.handle(
+                        &Event::ConnectionAttempt,
+                        EventData::ConnectionAttempt(
+                            ConnectionAttemptEvent::new()
+                                .attempt(attempt)
+                                .next_delay(err_sleep)
+                                .error(err.to_string()),
+                        ),
+                    );
 
                     thread::sleep(err_sleep);
                 }
-                _ => manager.handshake_completed = true,
+                _ => {
+                    attempt = 0;
+                    connected_once = true;
+                    manager.handshake_completed = true;
+                }
             },
         }
     }
 }
 
-fn send_and_receive(
-    connection: &mut Socket,
-    event_handler_registry: &Arc<HandlerRegistry>,
-    inbound: &mut Tx,
-    outbound: &Rx,
-) -> Result<()> {
+/// The state of a connection attempt, depending on whether the client was connected before
+fn attempt_state(connected_once: bool) -> ConnectionState {
+    if connected_once {
+        ConnectionState::Reconnecting
+    } else {
+        ConnectionState::Connecting
+    }
+}
+
+fn send_and_receive(connection: &mut Transport, manager: &Manager) -> Result<()> {
+    let Manager {
+        event_handler_registry,
+        internal_nonces,
+        interceptors,
+        ..
+    } = manager;
+    let inbound = &manager.inbound.1;
+    let outbound = &manager.outbound.0;
+
     while let Ok(msg) = outbound.try_recv() {
         trace!("Sending message");
         connection.send(&msg)?;
@@ -204,24 +445,83 @@ fn send_and_receive(
     }
 
     trace!("Receiving from connection");
-    let msg = connection.recv()?;
+    let mut msg = connection.recv()?;
+    let received_at = SystemTime::now();
     trace!("Received from connection");
 
-    let payload: Payload<JsonValue> = serde_json::from_str(&msg.payload)?;
+    // Kept as Discord sent it, before interceptors can modify it
+    let raw: JsonValue = serde_json::from_str(&msg.payload)?;
+    let mut payload: Payload<JsonValue> = serde_json::from_value(raw.clone())?;
 
     trace!("Received payload");
 
-    if let Payload {
-        evt: Some(event), ..
-    } = &payload
-    {
-        trace!("Got event");
-        let event_data = event.parse_data(into_error!(payload.data.clone())?);
-        event_handler_registry.handle(*event, event_data);
-    } else {
-        trace!("Got message");
-        inbound.send(msg)?;
+    if !interceptors.is_empty() {
+        if interceptors.inbound(&mut payload) == Action::Drop {
+            trace!("Inbound {:?} dropped by an interceptor", payload.cmd);
+
+            // Don't leave the caller waiting for a response that will never arrive
+            let is_response = payload.cmd != Command::Dispatch
+                && !payload
+                    .nonce
+                    .as_ref()
+                    .is_some_and(|nonce| internal_nonces.lock().remove(nonce));
+            if is_response {
+                send_response(
+                    inbound,
+                    Err(DiscordError::Intercepted {
+                        command: payload.cmd,
+                    }),
+                );
+            }
+
+            return Ok(());
+        }
+
+        msg = Message::new(msg.opcode, &payload)?;
+    }
+
+    // Command responses, including errors, are returned to the caller,
+    // everything else is dispatched to the event handlers
+    match payload {
+        Payload {
+            cmd: Command::Dispatch,
+            evt: Some(event),
+            data,
+            nonce,
+            ..
+        } => {
+            trace!("Got event");
+            let event_data = event.parse_data(into_error!(data)?);
+            let frame = Frame {
+                raw,
+                nonce,
+                cmd: Command::Dispatch,
+                received_at,
+            };
+            event_handler_registry.handle_frame(&event, event_data, Some(frame));
+        }
+        Payload {
+            nonce: Some(ref nonce),
+            ..
+        } if internal_nonces.lock().remove(nonce) => {
+            if payload.evt == Some(Event::Error) {
+                error!("Automatic {:?} failed: {:?}", payload.cmd, payload.data);
+            } else {
+                trace!("Automatic {:?} succeeded", payload.cmd);
+            }
+        }
+        _ => {
+            trace!("Got message");
+            send_response(inbound, Ok(msg));
+        }
     }
 
     Ok(())
 }
+
+fn send_response(inbound: &ResponseTx, response: Result<Message>) {
+    // The manager keeps the receiver, so this only fails if it is being dropped
+    if inbound.send(response).is_err() {
+        error!("Failed to return a command response");
+    }
+}
@@ -1,8 +1,11 @@
+#[macro_use]
 mod base;
 mod manager;
+mod transport;
 
 pub use base::Connection;
 pub use manager::Manager;
+pub use transport::Transport;
 
 cfg_if::cfg_if! {
     if #[cfg(unix)] {
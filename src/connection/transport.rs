@@ -0,0 +1,70 @@
+use std::{sync::Arc, thread, time};
+
+use super::{base, Connection, Socket};
+use crate::{
+    models::Message,
+    session::{Replay, SessionRecorder},
+    Result,
+};
+
+enum Kind {
+    Ipc(Socket),
+    Replay(Replay),
+}
+
+/// Where the manager sends its frames to, and receives them from, optionally recording them
+pub struct Transport {
+    kind: Kind,
+    recorder: Option<Arc<SessionRecorder>>,
+}
+
+impl Transport {
+    /// Connect to the replayed session if there is one, or to Discord otherwise
+    pub fn connect(
+        replay: Option<&Replay>,
+        recorder: Option<Arc<SessionRecorder>>,
+    ) -> Result<Self> {
+        let kind = match replay {
+            Some(replay) => Kind::Replay(replay.clone()),
+            None => Kind::Ipc(Socket::connect()?),
+        };
+
+        Ok(Self { kind, recorder })
+    }
+
+    /// Perform a handshake on this connection.
+    /// Will block until complete.
+    pub fn handshake(&mut self, client_id: u64) -> Result<Message> {
+        let msg = base::handshake_message(client_id)?;
+        try_until_done!(self.send(&msg));
+        let msg = try_until_done!(self.recv());
+
+        Ok(msg)
+    }
+
+    pub fn send(&mut self, message: &Message) -> Result<()> {
+        match self.kind {
+            Kind::Ipc(ref mut socket) => socket.send(message)?,
+            Kind::Replay(ref replay) => replay.send(message),
+        }
+
+        if let Some(ref recorder) = self.recorder {
+            recorder.sent(message);
+        }
+
+        Ok(())
+    }
+
+    pub fn recv(&mut self) -> Result<Message> {
+        let message = match self.kind {
+            Kind::Ipc(ref mut socket) => socket.recv()?,
+            Kind::Replay(ref replay) => replay.recv()?,
+        };
+
+        if let Some(ref recorder) = self.recorder {
+            recorder.received(&message);
+        }
+
+        Ok(message)
+    }
+}
@@ -4,9 +4,112 @@ use std::{
     io::Error as IoError,
     result::Result as StdResult,
     sync::mpsc::{RecvError as ChannelRecv, RecvTimeoutError as ChannelTimeout},
+    time::Duration,
 };
 
-use crate::models::Message;
+use crate::models::{Command, Event, Message};
+
+/// Error codes Discord responds with when a command fails
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RpcErrorCode {
+    /// An unknown error occurred
+    UnknownError,
+    /// The payload was invalid
+    InvalidPayload,
+    /// The command name was invalid
+    InvalidCommand,
+    /// The guild id was invalid
+    InvalidGuild,
+    /// The event name was invalid
+    InvalidEvent,
+    /// The channel id was invalid
+    InvalidChannel,
+    /// The client is not authenticated, or lacks the required scope or permissions
+    NotAuthenticated,
+    /// The client id was invalid
+    InvalidClientId,
+    /// The origin was invalid
+    InvalidOrigin,
+    /// The `OAuth2` token was invalid
+    InvalidToken,
+    /// The user id was invalid
+    InvalidUser,
+    /// A standard `OAuth2` error occurred
+    OAuth2Error,
+    /// An asynchronous `SELECT_TEXT_CHANNEL`/`SELECT_VOICE_CHANNEL` command timed out
+    SelectChannelTimedOut,
+    /// An asynchronous `GET_GUILD` command timed out
+    GetGuildTimedOut,
+    /// The user is already in a voice channel, and the `force` option was not set
+    SelectVoiceForceRequired,
+    /// The user is already capturing a shortcut
+    CaptureShortcutAlreadyListening,
+    /// An error code that is not known by this crate
+    Other(u32),
+}
+
+impl From<u32> for RpcErrorCode {
+    fn from(code: u32) -> Self {
+        match code {
+            1000 => Self::UnknownError,
+            4000 => Self::InvalidPayload,
+            4002 => Self::InvalidCommand,
+            4003 => Self::InvalidGuild,
+            4004 => Self::InvalidEvent,
+            4005 => Self::InvalidChannel,
+            4006 => Self::NotAuthenticated,
+            4007 => Self::InvalidClientId,
+            4008 => Self::InvalidOrigin,
+            4009 => Self::InvalidToken,
+            4010 => Self::InvalidUser,
+            5000 => Self::OAuth2Error,
+            5001 => Self::SelectChannelTimedOut,
+            5002 => Self::GetGuildTimedOut,
+            5003 => Self::SelectVoiceForceRequired,
+            5004 => Self::CaptureShortcutAlreadyListening,
+            code => Self::Other(code),
+        }
+    }
+}
+
+impl From<RpcErrorCode> for u32 {
+    fn from(code: RpcErrorCode) -> Self {
+        match code {
+            RpcErrorCode::UnknownError => 1000,
+            RpcErrorCode::InvalidPayload => 4000,
+            RpcErrorCode::InvalidCommand => 4002,
+            RpcErrorCode::InvalidGuild => 4003,
+            RpcErrorCode::InvalidEvent => 4004,
+            RpcErrorCode::InvalidChannel => 4005,
+            RpcErrorCode::NotAuthenticated => 4006,
+            RpcErrorCode::InvalidClientId => 4007,
+            RpcErrorCode::InvalidOrigin => 4008,
+            RpcErrorCode::InvalidToken => 4009,
+            RpcErrorCode::InvalidUser => 4010,
+            RpcErrorCode::OAuth2Error => 5000,
+            RpcErrorCode::SelectChannelTimedOut => 5001,
+            RpcErrorCode::GetGuildTimedOut => 5002,
+            RpcErrorCode::SelectVoiceForceRequired => 5003,
+            RpcErrorCode::CaptureShortcutAlreadyListening => 5004,
+            RpcErrorCode::Other(code) => code,
+        }
+    }
+}
+
+/// How an error should be handled, see [`DiscordError::category`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    /// Discord is not running, or its IPC socket could not be found
+    ///
+    /// Retrying once Discord has started may succeed
+    NotRunning,
+    /// A temporary error, such as a dropped connection or a timeout
+    ///
+    /// Retrying may succeed
+    Retryable,
+    /// An error that will not be resolved by retrying, such as an invalid client id
+    Fatal,
+}
 
 /// Error types from Discord
 #[derive(Debug, thiserror::Error)]
@@ -51,12 +154,44 @@ pub enum DiscordError {
     #[error("Header was received, but no message was received")]
     /// Header was received, but no message was received
     NoMessage,
-    #[error("Error subscribing to an event")]
-    /// Subscription Joining Error
-    SubscriptionFailed,
+    #[error("Discord responded to {command:?} with error {code:?}: {message}")]
+    /// Discord responded to a command with an error
+    Rpc {
+        /// The error code
+        code: RpcErrorCode,
+        /// The error message
+        message: String,
+        /// The command that failed
+        command: Command,
+    },
+    #[error("Discord rejected the connection with error {code}: {message}")]
+    /// Discord closed the connection during the handshake, for example because the client id is invalid
+    ConnectionRejected {
+        /// The close code
+        code: u32,
+        /// The close message
+        message: String,
+    },
     #[error("Connection was closed prematurely")]
     /// Connection Closing error
     ConnectionClosed,
+    #[error("The client gave up connecting to Discord")]
+    /// The client gave up connecting to Discord, see [`Client::block_on`](crate::Client::block_on) for the underlying error
+    ConnectionFailed,
+    #[error("Timed out after {timeout:?} waiting for {events:?}")]
+    /// None of the awaited events were fired in time, see [`Client::wait_for`](crate::Client::wait_for)
+    EventTimeout {
+        /// The events that were awaited
+        events: Vec<Event>,
+        /// How long the client waited
+        timeout: Duration,
+    },
+    #[error("{command:?} was dropped by an interceptor")]
+    /// An interceptor dropped the command, or its response, see [`Interceptor`](crate::interceptor::Interceptor)
+    Intercepted {
+        /// The command that was dropped
+        command: Command,
+    },
     #[error("Connection has not been started")]
     /// Connection has not been started
     NotStarted,
@@ -69,6 +204,18 @@ pub enum DiscordError {
     #[error("Could not safely shut down client. Thread is in use.")]
     /// RPC thread is in use
     ThreadInUse,
+    #[error("{field} must be between {min} and {max}, got {value}")]
+    /// A value passed to a command is outside of the range accepted by Discord
+    OutOfRange {
+        /// The name of the field
+        field: &'static str,
+        /// The value that was given
+        value: f64,
+        /// The minimum accepted value
+        min: f64,
+        /// The maximum accepted value
+        max: f64,
+    },
 }
 
 impl DiscordError {
@@ -83,13 +230,117 @@ impl DiscordError {
 
     #[must_use]
     /// Checks if the error should break the connection
+    #[deprecated(note = "use `DiscordError::category` or `DiscordError::is_fatal` instead")]
     pub fn should_break(&self) -> bool {
+        self.is_fatal()
+    }
+
+    #[must_use]
+    /// Classify the error, to decide whether the failed action should be retried
+    pub fn category(&self) -> ErrorCategory {
+        use std::io::ErrorKind;
+
         match self {
-            Self::IoError(ref err) => err.kind() == std::io::ErrorKind::ConnectionRefused,
-            _ => false,
+            Self::IoError(ref err) => match err.kind() {
+                ErrorKind::NotFound | ErrorKind::ConnectionRefused => ErrorCategory::NotRunning,
+                ErrorKind::PermissionDenied => ErrorCategory::Fatal,
+                _ => ErrorCategory::Retryable,
+            },
+            Self::Rpc { code, .. } => match code {
+                RpcErrorCode::UnknownError
+                | RpcErrorCode::SelectChannelTimedOut
+                | RpcErrorCode::GetGuildTimedOut => ErrorCategory::Retryable,
+                _ => ErrorCategory::Fatal,
+            },
+            Self::MPSCTimeout(_)
+            | Self::TimeoutError(_)
+            | Self::HeaderLength
+            | Self::NoMessage
+            | Self::ConnectionClosed
+            | Self::EventTimeout { .. }
+            | Self::JsonError(_)
+            | Self::NotStarted => ErrorCategory::Retryable,
+            Self::SendMessage(_)
+            | Self::CloseError(_)
+            | Self::ReceiveError(_)
+            | Self::MPSCReceiveError(_)
+            | Self::ThreadError
+            | Self::NoneError(_)
+            | Self::Conversion
+            | Self::ConnectionRejected { .. }
+            | Self::ConnectionFailed
+            | Self::EventLoopError
+            | Self::NoChangesMade
+            | Self::ThreadInUse
+            | Self::Intercepted { .. }
+            | Self::OutOfRange { .. } => ErrorCategory::Fatal,
         }
     }
+
+    #[must_use]
+    /// Checks if retrying the failed action may succeed
+    pub fn is_retryable(&self) -> bool {
+        self.category() == ErrorCategory::Retryable
+    }
+
+    #[must_use]
+    /// Checks if the error will not be resolved by retrying
+    pub fn is_fatal(&self) -> bool {
+        self.category() == ErrorCategory::Fatal
+    }
+
+    #[must_use]
+    /// Checks if the error was caused by Discord not running
+    pub fn is_not_running(&self) -> bool {
+        self.category() == ErrorCategory::NotRunning
+    }
 }
 
 /// Result type for Discord RPC error types
 pub type Result<T> = StdResult<T, DiscordError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_convert_rpc_error_codes() {
+        assert_eq!(RpcErrorCode::from(4000), RpcErrorCode::InvalidPayload);
+        assert_eq!(RpcErrorCode::from(4006), RpcErrorCode::NotAuthenticated);
+        assert_eq!(RpcErrorCode::from(4242), RpcErrorCode::Other(4242));
+
+        assert_eq!(u32::from(RpcErrorCode::InvalidClientId), 4007);
+        assert_eq!(u32::from(RpcErrorCode::Other(4242)), 4242);
+    }
+
+    #[test]
+    fn can_categorize_errors() {
+        use std::io::ErrorKind;
+
+        let refused = DiscordError::from(IoError::from(ErrorKind::ConnectionRefused));
+        assert!(refused.is_not_running());
+        assert!(!refused.is_fatal());
+
+        let missing = DiscordError::from(IoError::from(ErrorKind::NotFound));
+        assert_eq!(missing.category(), ErrorCategory::NotRunning);
+
+        assert!(DiscordError::ConnectionClosed.is_retryable());
+
+        // A malformed frame does not mean that the next connection will fail
+        let malformed = DiscordError::from(serde_json::from_str::<u32>("{").unwrap_err());
+        assert!(malformed.is_retryable());
+
+        let rejected = DiscordError::ConnectionRejected {
+            code: 4000,
+            message: String::from("Invalid Client ID"),
+        };
+        assert!(rejected.is_fatal());
+
+        let invalid_payload = DiscordError::Rpc {
+            code: RpcErrorCode::InvalidPayload,
+            message: String::new(),
+            command: Command::SetActivity,
+        };
+        assert!(invalid_payload.is_fatal());
+    }
+}
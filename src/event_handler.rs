@@ -1,25 +1,188 @@
-use std::{collections::HashMap, sync::Arc};
-use std::{sync::Weak, thread};
+use std::sync::Weak;
+use std::{
+    any::Any,
+    collections::HashMap,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::SystemTime,
+};
 
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
+use serde_json::Value as JsonValue;
 
-use crate::models::{Event, EventData};
+use crate::{
+    event_stream::EventSender,
+    executor::{HandlerExecutor, Runner},
+    models::{Command, Event, EventData},
+};
 
 /// Event handler callback
 pub type Handler = dyn Fn(Context) + 'static + Send + Sync;
 
-type HandlerList = Vec<Arc<Handler>>;
+/// Wildcard event handler callback, see [`Client::on_any`](crate::Client::on_any)
+pub type AnyHandler = dyn Fn(Event, Context) + 'static + Send + Sync;
+
+/// Callback for panicking event handlers, see [`Client::with_panic_handler`](crate::Client::with_panic_handler)
+pub type PanicHandler = dyn Fn(HandlerPanic) + 'static + Send + Sync;
+
+static NEXT_HANDLER_ID: AtomicU64 = AtomicU64::new(0);
+
+/// A report of an event handler that panicked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandlerPanic {
+    /// The event that was being handled
+    pub event: Event,
+    /// The id of the handler, see [`EventCallbackHandle::id`]
+    pub handler_id: u64,
+    /// The panic message, if it was a string
+    pub message: String,
+    /// How many times the handler has panicked
+    pub panics: usize,
+    /// Whether the handler was unregistered, because it panicked too many times
+    pub disabled: bool,
+}
+
+/// A handler, along with the options it was registered with
+struct Registered {
+    handler: Arc<Handler>,
+    id: u64,
+    panics: AtomicUsize,
+    priority: i32,
+    once: bool,
+    /// Whether a `once` handler has been called
+    fired: AtomicBool,
+    /// Whether the handler holds a reference to the automatic subscription of its event
+    subscribed: bool,
+}
+
+/// Automatic subscriptions to server-side events
+#[derive(Default)]
+struct Subscriptions {
+    /// The number of handlers of each event, and of manual subscriptions to it
+    counts: HashMap<Event, usize>,
+    /// Subscriptions that changed since they were last sent
+    pending: Vec<(Command, Event)>,
+}
+
+type HandlerList = Vec<Arc<Registered>>;
+
+/// A wildcard handler, see [`Registered`]
+struct AnyRegistered {
+    handler: Arc<AnyHandler>,
+    id: u64,
+    panics: AtomicUsize,
+}
+
+/// A frame received from Discord, which an event was parsed from
+pub(crate) struct Frame {
+    pub raw: JsonValue,
+    pub nonce: Option<String>,
+    pub cmd: Command,
+    pub received_at: SystemTime,
+}
 
 #[derive(Debug, Clone)]
 /// Event context
 pub struct Context {
     /// Event data
     pub event: EventData,
+    /// The raw payload the event was parsed from
+    ///
+    /// This is the payload as Discord sent it, even if an [`Interceptor`](crate::interceptor::Interceptor) modified the event.
+    /// `None` for events fired by the client itself, such as [`Event::Connected`] or [`Event::ConnectionAttempt`].
+    pub raw: Option<JsonValue>,
+    /// The nonce of the payload, if it had one
+    pub nonce: Option<String>,
+    /// The command of the payload, usually [`Command::Dispatch`]
+    ///
+    /// `None` for events fired by the client itself.
+    pub cmd: Option<Command>,
+    /// When the event was received, or fired by the client itself
+    pub received_at: SystemTime,
+    /// The connection the event was received on
+    ///
+    /// Starts at 1, and is incremented every time the client connects to Discord,
+    /// so events of a previous connection can be told apart. 0 if the client never connected.
+    pub generation: u64,
+    consumed: Arc<AtomicBool>,
 }
 
 impl Context {
-    pub(crate) fn new(event: EventData) -> Self {
-        Self { event }
+    pub(crate) fn new(event: EventData, frame: Option<Frame>, generation: u64) -> Self {
+        let (raw, nonce, cmd, received_at) = match frame {
+            Some(frame) => (
+                Some(frame.raw),
+                frame.nonce,
+                Some(frame.cmd),
+                frame.received_at,
+            ),
+            None => (None, None, None, SystemTime::now()),
+        };
+
+        Self {
+            event,
+            raw,
+            nonce,
+            cmd,
+            received_at,
+            generation,
+            consumed: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// A copy of the context, which has not been consumed
+    fn fresh(&self) -> Self {
+        Self {
+            consumed: Arc::new(AtomicBool::new(false)),
+            ..self.clone()
+        }
+    }
+
+    /// Stop the event from reaching handlers with a lower priority, see [`HandlerOptions::priority`]
+    pub fn consume(&self) {
+        self.consumed.store(true, Ordering::SeqCst);
+    }
+
+    #[must_use]
+    /// Whether a handler consumed the event
+    pub fn is_consumed(&self) -> bool {
+        self.consumed.load(Ordering::SeqCst)
+    }
+}
+
+/// Options of an event handler, see [`Client::on_event_with`](crate::Client::on_event_with)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct HandlerOptions {
+    priority: i32,
+    once: bool,
+}
+
+impl HandlerOptions {
+    #[must_use]
+    /// The default options: priority 0, called for every event
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    /// Handlers with a higher priority are called first, handlers with the same priority in the order they were registered
+    ///
+    /// When an event has handlers of several priorities, they are run one after another, highest priority first,
+    /// so that a handler can [`consume`](Context::consume) the event to stop it from reaching lower priorities.
+    /// Handlers of a single priority are handed to the executor independently, as usual.
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    #[must_use]
+    /// Unregister the handler after it is called for the first time
+    pub fn once(mut self) -> Self {
+        self.once = true;
+        self
     }
 }
 
@@ -28,12 +191,25 @@ type Handlers = RwLock<HashMap<Event, HandlerList>>;
 #[must_use = "event listeners will be immediately dropped if the handle is not kept. Use `.persist` to stop them from being removed."]
 /// Handle to an event listener
 pub struct EventCallbackHandle {
-    event: Event,
+    id: u64,
+    slot: Slot,
     registry: Weak<HandlerRegistry>,
-    handler: Weak<Handler>,
+    once: bool,
+}
+
+/// Where a handler is registered
+enum Slot {
+    Event(Event, Weak<Registered>),
+    Any(Weak<AnyRegistered>),
 }
 
 impl EventCallbackHandle {
+    #[must_use]
+    /// A unique id of the handler, used to identify it in a [`HandlerPanic`]
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
     /// Immediately drops the event handler, thus removing the handler from the registry.
     pub fn remove(self) {
         drop(self);
@@ -43,62 +219,513 @@ impl EventCallbackHandle {
     pub fn persist(self) {
         std::mem::forget(self);
     }
+
+    /// Removes the handler from the registry, `None` if the registry or the handler no longer exist
+    fn unregister(&self) -> Option<crate::Result<()>> {
+        let registry = self.registry.upgrade()?;
+
+        let (removed, panics) = match self.slot {
+            Slot::Event(ref event, ref handler) => {
+                let handler = handler.upgrade()?;
+                let removed = registry.remove(event, &handler.handler).map(drop);
+                (removed, handler.panics.load(Ordering::SeqCst))
+            }
+            Slot::Any(ref handler) => {
+                let handler = handler.upgrade()?;
+                let removed = registry.remove_any(&handler.handler);
+                (removed, handler.panics.load(Ordering::SeqCst))
+            }
+        };
+
+        // `once` handlers remove themselves after they are called, and handlers are removed once they are disabled
+        if removed.is_err() && (self.once || registry.is_disabled(panics)) {
+            return Some(Ok(()));
+        }
+
+        Some(removed)
+    }
 }
 
 impl Drop for EventCallbackHandle {
     fn drop(&mut self) {
         // if the registry or this event handler has already been dropped, there's no reason to try and do it again
-        if let (Some(registry), Some(handler)) = (self.registry.upgrade(), self.handler.upgrade()) {
-            let handler = registry.remove(self.event, &handler);
-            if handler.is_err() {
-                error!("Failed to remove event handler. This can usually be ignored.");
-            }
+        if matches!(self.unregister(), Some(Err(_))) {
+            error!("Failed to remove event handler. This can usually be ignored.");
         }
     }
 }
 
 pub(crate) struct HandlerRegistry {
     handlers: Handlers,
+    /// Handlers that receive every event
+    any: RwLock<Vec<Arc<AnyRegistered>>>,
+    /// Context of the sticky events whose state still holds
+    sticky: RwLock<HashMap<Event, Context>>,
+    /// The connection generation, see [`Context::generation`]
+    generation: AtomicU64,
+    /// Whether sticky events are replayed to every newly registered handler
+    replay_sticky: AtomicBool,
+    runner: RwLock<Runner>,
+    subscriptions: Mutex<Subscriptions>,
+    auto_subscribe: AtomicBool,
+    panic_handler: RwLock<Option<Arc<PanicHandler>>>,
+    /// Number of panics after which a handler is unregistered
+    max_panics: RwLock<Option<usize>>,
+    /// Senders of the [`EventReceiver`](crate::event_stream::EventReceiver)s, which receive every event
+    streams: RwLock<Vec<Arc<EventSender>>>,
 }
 
 impl HandlerRegistry {
     pub fn new() -> Self {
         Self {
             handlers: RwLock::new(HashMap::new()),
+            any: RwLock::new(Vec::new()),
+            sticky: RwLock::new(HashMap::new()),
+            generation: AtomicU64::new(0),
+            replay_sticky: AtomicBool::new(false),
+            runner: RwLock::new(Runner::new(HandlerExecutor::default())),
+            streams: RwLock::new(Vec::new()),
+            subscriptions: Mutex::new(Subscriptions::default()),
+            auto_subscribe: AtomicBool::new(true),
+            panic_handler: RwLock::new(None),
+            max_panics: RwLock::new(None),
+        }
+    }
+
+    pub fn set_panic_handler(&self, handler: Arc<PanicHandler>) {
+        *self.panic_handler.write() = Some(handler);
+    }
+
+    pub fn set_max_panics(&self, max_panics: Option<usize>) {
+        *self.max_panics.write() = max_panics;
+    }
+
+    pub fn set_auto_subscribe(&self, auto_subscribe: bool) {
+        self.auto_subscribe.store(auto_subscribe, Ordering::Relaxed);
+    }
+
+    /// Takes the subscriptions that changed since they were last sent
+    pub fn take_pending_subscriptions(&self) -> Vec<(Command, Event)> {
+        std::mem::take(&mut self.subscriptions.lock().pending)
+    }
+
+    /// Gets every event with handlers, to subscribe to them on a new connection
+    ///
+    /// Pending changes are dropped, as the new connection has no subscriptions yet.
+    pub fn active_subscriptions(&self) -> Vec<Event> {
+        let mut subscriptions = self.subscriptions.lock();
+        subscriptions.pending.clear();
+
+        subscriptions.counts.keys().cloned().collect()
+    }
+
+    /// Adds a reference to the subscription of `event`, queueing SUBSCRIBE for the first one
+    pub fn add_subscription(&self, event: &Event) {
+        let mut subscriptions = self.subscriptions.lock();
+        let count = subscriptions.counts.entry(event.clone()).or_default();
+        *count += 1;
+
+        if *count == 1 {
+            subscriptions
+                .pending
+                .push((Command::Subscribe, event.clone()));
         }
     }
 
+    /// Removes a reference to the subscription of `event`, queueing UNSUBSCRIBE for the last one
+    pub fn remove_subscription(&self, event: &Event) {
+        let mut subscriptions = self.subscriptions.lock();
+        if let Some(count) = subscriptions.counts.get_mut(event) {
+            *count -= 1;
+
+            if *count == 0 {
+                subscriptions.counts.remove(event);
+                subscriptions
+                    .pending
+                    .push((Command::Unsubscribe, event.clone()));
+            }
+        }
+    }
+
+    /// Counts a manual subscription to `event`, returns whether SUBSCRIBE needs to be sent
+    ///
+    /// Any pending change is dropped, as the manual command is sent instead.
+    pub fn acquire_subscription(&self, event: &Event) -> bool {
+        let mut subscriptions = self.subscriptions.lock();
+        let count = subscriptions.counts.entry(event.clone()).or_default();
+        *count += 1;
+
+        let first = *count == 1;
+        if first {
+            subscriptions
+                .pending
+                .retain(|(_, pending)| pending != event);
+        }
+
+        first
+    }
+
+    /// Counts a manual unsubscription from `event`, returns whether UNSUBSCRIBE needs to be sent
+    ///
+    /// `None` if the event was not subscribed to, in which case nothing was counted.
+    pub fn release_subscription(&self, event: &Event) -> Option<bool> {
+        let mut subscriptions = self.subscriptions.lock();
+        let count = subscriptions.counts.get_mut(event)?;
+        *count -= 1;
+
+        let last = *count == 0;
+        if last {
+            subscriptions.counts.remove(event);
+            subscriptions
+                .pending
+                .retain(|(_, pending)| pending != event);
+        }
+
+        Some(last)
+    }
+
+    pub fn add_stream(&self, stream: Arc<EventSender>) {
+        self.streams.write().push(stream);
+    }
+
+    pub fn remove_stream(&self, target: &Arc<EventSender>) {
+        self.streams
+            .write()
+            .retain(|stream| !Arc::ptr_eq(stream, target));
+    }
+
+    pub fn set_executor(&self, executor: HandlerExecutor) {
+        *self.runner.write() = Runner::new(executor);
+    }
+
+    /// Starts a new connection generation, see [`Context::generation`]
+    pub fn next_generation(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Events that describe a state, rather than something that happened once
+    fn is_sticky(event: &Event) -> bool {
+        matches!(event, Event::Ready | Event::Connected)
+    }
+
+    pub fn set_replay_sticky(&self, replay: bool) {
+        self.replay_sticky.store(replay, Ordering::Relaxed);
+    }
+
+    /// Forget the data of a sticky event, once its state no longer holds
+    pub fn clear_sticky(&self, event: &Event) {
+        self.sticky.write().remove(event);
+    }
+
+    /// Remember the data of a sticky event whose state holds again, without handling the event
+    pub fn set_sticky(&self, event: &Event, data: EventData, frame: Option<Frame>) {
+        let context = Context::new(data, frame, self.generation.load(Ordering::SeqCst));
+        self.sticky.write().insert(event.clone(), context);
+    }
+
     pub fn register<F>(self: &Arc<Self>, event: Event, handler: F) -> EventCallbackHandle
     where
         F: Fn(Context) + Send + Sync + 'static,
     {
-        let handler: Arc<Handler> = Arc::new(handler);
-        let callback_handle = EventCallbackHandle {
-            event,
+        self.register_with(event, handler, HandlerOptions::default())
+    }
+
+    pub fn register_with<F>(
+        self: &Arc<Self>,
+        event: Event,
+        handler: F,
+        options: HandlerOptions,
+    ) -> EventCallbackHandle
+    where
+        F: Fn(Context) + Send + Sync + 'static,
+    {
+        let replay = self.replay_sticky.load(Ordering::Relaxed);
+        self.register_with_replay(event, handler, options, replay)
+    }
+
+    /// Registers a handler, which is immediately called if `event` is sticky and its state still holds
+    pub fn register_sticky<F>(self: &Arc<Self>, event: Event, handler: F) -> EventCallbackHandle
+    where
+        F: Fn(Context) + Send + Sync + 'static,
+    {
+        self.register_with_replay(event, handler, HandlerOptions::default(), true)
+    }
+
+    fn register_with_replay<F>(
+        self: &Arc<Self>,
+        event: Event,
+        handler: F,
+        options: HandlerOptions,
+        replay: bool,
+    ) -> EventCallbackHandle
+    where
+        F: Fn(Context) + Send + Sync + 'static,
+    {
+        let subscribed =
+            self.auto_subscribe.load(Ordering::Relaxed) && event.is_auto_subscribable();
+        if subscribed {
+            self.add_subscription(&event);
+        }
+
+        let handler = Arc::new(Registered {
+            handler: Arc::new(handler),
+            id: NEXT_HANDLER_ID.fetch_add(1, Ordering::Relaxed),
+            panics: AtomicUsize::new(0),
+            priority: options.priority,
+            once: options.once,
+            fired: AtomicBool::new(false),
+            subscribed,
+        });
+
+        // The sticky data is read while holding the handlers lock, which `handle` also holds while updating it,
+        // so an event dispatched before the handler was registered is only replayed.
+        // The replay is dispatched after the lock is released though, so an event dispatched in between
+        // may reach the handler before the replayed one, which may be a copy of the same event.
+        let replayed = {
+            let mut event_handlers = self.handlers.write();
+
+            let replayed = if replay && Self::is_sticky(&event) {
+                self.sticky.read().get(&event).cloned()
+            } else {
+                None
+            };
+
+            // Sorted by priority, and then by registration order
+            let event_handler = event_handlers.entry(event.clone()).or_default();
+            let index = event_handler.partition_point(|other| other.priority >= handler.priority);
+            event_handler.insert(index, handler.clone());
+
+            replayed
+        };
+
+        // Handlers are run after the lock is released, so that they can register other handlers
+        if let Some(data) = replayed {
+            self.dispatch(&event, vec![handler.clone()], &data.fresh());
+        }
+
+        EventCallbackHandle {
+            id: handler.id,
+            slot: Slot::Event(event, Arc::downgrade(&handler)),
             registry: Arc::downgrade(self),
-            handler: Arc::downgrade(&handler),
+            once: options.once,
+        }
+    }
+
+    /// Registers a handler that receives every event, including events that are not natively supported
+    pub fn register_any<F>(self: &Arc<Self>, handler: F) -> EventCallbackHandle
+    where
+        F: Fn(Event, Context) + Send + Sync + 'static,
+    {
+        let handler = Arc::new(AnyRegistered {
+            handler: Arc::new(handler),
+            id: NEXT_HANDLER_ID.fetch_add(1, Ordering::Relaxed),
+            panics: AtomicUsize::new(0),
+        });
+
+        // Same locking as `register_with_replay`, with the same caveats
+        let replayed: Vec<(Event, Context)> = {
+            let _handlers = self.handlers.write();
+            self.any.write().push(handler.clone());
+
+            if self.replay_sticky.load(Ordering::Relaxed) {
+                self.sticky
+                    .read()
+                    .iter()
+                    .map(|(event, context)| (event.clone(), context.fresh()))
+                    .collect()
+            } else {
+                Vec::new()
+            }
+        };
+
+        for (event, context) in replayed {
+            self.dispatch_any(&event, vec![handler.clone()], &context);
+        }
+
+        EventCallbackHandle {
+            id: handler.id,
+            slot: Slot::Any(Arc::downgrade(&handler)),
+            registry: Arc::downgrade(self),
+            once: false,
+        }
+    }
+
+    /// Handles an event fired by the client itself
+    pub fn handle(self: &Arc<Self>, event: &Event, data: EventData) {
+        self.handle_frame(event, data, None);
+    }
+
+    /// Handles an event, which was parsed from `frame` if it was received from Discord
+    pub fn handle_frame(self: &Arc<Self>, event: &Event, data: EventData, frame: Option<Frame>) {
+        let context = Context::new(data, frame, self.generation.load(Ordering::SeqCst));
+
+        let (handlers, any) = {
+            let handlers = self.handlers.read();
+
+            if Self::is_sticky(event) {
+                self.sticky.write().insert(event.clone(), context.clone());
+            }
+
+            (
+                handlers.get(event).cloned().unwrap_or_default(),
+                self.any.read().clone(),
+            )
         };
 
-        let mut event_handlers = self.handlers.write();
-        let event_handler = event_handlers.entry(event).or_default();
-        event_handler.push(handler);
+        // Streams are fed before the handlers run, as inline handlers could take a while
+        let streams = self.streams.read().clone();
+        for stream in streams {
+            stream.send(event, &context.event);
+        }
 
-        callback_handle
+        self.dispatch(event, handlers, &context);
+        self.dispatch_any(event, any, &context);
     }
 
-    // TODO: Replace data type with stronger types
-    pub fn handle(&self, event: Event, data: EventData) {
-        let handlers = self.handlers.read();
-        if let Some(handlers) = handlers.get(&event) {
-            let context = Context::new(data);
+    /// Hands the wildcard handlers to the executor
+    ///
+    /// Wildcard handlers are not affected by priorities, so they receive consumed events too.
+    fn dispatch_any(
+        self: &Arc<Self>,
+        event: &Event,
+        any: Vec<Arc<AnyRegistered>>,
+        context: &Context,
+    ) {
+        let runner = self.runner.read();
 
+        for handler in any {
+            let registry = Arc::downgrade(self);
+            let event = event.clone();
+            let context = context.fresh();
+            runner.execute(Box::new(move || {
+                let target = handler.handler.clone();
+                Self::guarded(
+                    &registry,
+                    &event,
+                    handler.id,
+                    &handler.panics,
+                    || (handler.handler)(event.clone(), context),
+                    |registry| drop(registry.remove_any(&target)),
+                );
+            }));
+        }
+    }
+
+    /// Hands the handlers to the executor, in order
+    fn dispatch(self: &Arc<Self>, event: &Event, handlers: HandlerList, context: &Context) {
+        let runner = self.runner.read();
+        let registry = Arc::downgrade(self);
+
+        let single_priority = handlers
+            .windows(2)
+            .all(|pair| pair[0].priority == pair[1].priority);
+
+        if single_priority {
             for handler in handlers {
-                let handler = handler.clone();
+                let registry = registry.clone();
+                let event = event.clone();
                 let context = context.clone();
-                thread::spawn(move || {
-                    handler(context);
-                });
+                runner.execute(Box::new(move || {
+                    Self::call(&registry, &event, &handler, context);
+                }));
+            }
+        } else {
+            // Run as a single job, so that lower priorities only run once higher priorities are done
+            let event = event.clone();
+            let context = context.clone();
+            runner.execute(Box::new(move || {
+                let mut priority = None;
+                for handler in handlers {
+                    // Handlers with the same priority as the one that consumed the event are still called
+                    if context.is_consumed() && priority != Some(handler.priority) {
+                        break;
+                    }
+                    priority = Some(handler.priority);
+
+                    Self::call(&registry, &event, &handler, context.clone());
+                }
+            }));
+        }
+    }
+
+    fn call(registry: &Weak<Self>, event: &Event, handler: &Registered, context: Context) {
+        if handler.once {
+            // The handler may have been dispatched again before it was removed
+            if handler.fired.swap(true, Ordering::SeqCst) {
+                return;
+            }
+
+            if let Some(registry) = registry.upgrade() {
+                let _ = registry.remove(event, &handler.handler);
+            }
+        }
+
+        Self::guarded(
+            registry,
+            event,
+            handler.id,
+            &handler.panics,
+            || (handler.handler)(context),
+            |registry| drop(registry.remove(event, &handler.handler)),
+        );
+    }
+
+    /// Runs a handler, reporting it if it panics, and disabling it if it panicked too many times
+    fn guarded<F, D>(
+        registry: &Weak<Self>,
+        event: &Event,
+        id: u64,
+        panics: &AtomicUsize,
+        handler: F,
+        disable: D,
+    ) where
+        F: FnOnce(),
+        D: FnOnce(&Arc<Self>),
+    {
+        let Err(payload) = panic::catch_unwind(AssertUnwindSafe(handler)) else {
+            return;
+        };
+        let Some(registry) = registry.upgrade() else {
+            return;
+        };
+
+        let panics = panics.fetch_add(1, Ordering::SeqCst) + 1;
+        let disabled = registry.is_disabled(panics);
+        if disabled {
+            disable(&registry);
+        }
+
+        registry.report_panic(HandlerPanic {
+            event: event.clone(),
+            handler_id: id,
+            message: panic_message(payload.as_ref()),
+            panics,
+            disabled,
+        });
+    }
+
+    /// Whether a handler that panicked `panics` times is disabled
+    fn is_disabled(&self, panics: usize) -> bool {
+        self.max_panics
+            .read()
+            .is_some_and(|max_panics| panics >= max_panics)
+    }
+
+    fn report_panic(&self, report: HandlerPanic) {
+        let panic_handler = self.panic_handler.read().clone();
+
+        if let Some(panic_handler) = panic_handler {
+            if panic::catch_unwind(AssertUnwindSafe(|| panic_handler(report))).is_err() {
+                error!("The handler panic callback panicked");
             }
+        } else {
+            error!(
+                "Event handler {} panicked while handling {:?}: {}{}",
+                report.handler_id,
+                report.event,
+                report.message,
+                if report.disabled { ", disabled it" } else { "" }
+            );
         }
     }
 
@@ -109,21 +736,52 @@ impl HandlerRegistry {
     // TODO: Change return type to Result
     pub fn remove(
         self: &Arc<Self>,
-        event: Event,
+        event: &Event,
         target: &Arc<Handler>,
     ) -> crate::Result<Arc<Handler>> {
         let mut handlers = self.handlers.write();
-        if let Some(handlers) = handlers.get_mut(&event) {
+        if let Some(handlers) = handlers.get_mut(event) {
             if let Some(index) = handlers
                 .iter()
-                .position(|handler| Arc::ptr_eq(handler, target))
+                .position(|registered| Arc::ptr_eq(&registered.handler, target))
             {
-                return Ok(handlers.remove(index));
+                let removed = handlers.remove(index);
+                if removed.subscribed {
+                    self.remove_subscription(event);
+                }
+
+                return Ok(removed.handler.clone());
             }
         }
 
         Err(crate::DiscordError::NoChangesMade)
     }
+
+    /// Removes a wildcard handler from the registry, if it exists
+    ///
+    /// # Errors
+    /// - Returns an error if no changes were made to the registry, see [`HandlerRegistry::remove`]
+    pub fn remove_any(&self, target: &Arc<AnyHandler>) -> crate::Result<()> {
+        let mut any = self.any.write();
+        let count = any.len();
+        any.retain(|registered| !Arc::ptr_eq(&registered.handler, target));
+
+        if any.len() == count {
+            Err(crate::DiscordError::NoChangesMade)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        (*message).to_owned()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Box<dyn Any>".to_owned()
+    }
 }
 
 #[cfg(test)]
@@ -180,4 +838,262 @@ mod tests {
         assert_eq!(handlers.len(), 1);
         assert_eq!(handlers[&Event::Ready].len(), 1);
     }
+
+    fn recv_context(rx: &crossbeam_channel::Receiver<Context>) -> Option<Context> {
+        rx.recv_timeout(std::time::Duration::from_millis(200)).ok()
+    }
+
+    /// Late handlers receive sticky events, while their state still holds
+    #[test]
+    fn replays_sticky_events() {
+        let registry = Arc::new(HandlerRegistry::new());
+        registry.set_replay_sticky(true);
+        registry.handle(&Event::Ready, EventData::None);
+        registry.handle(&Event::Connected, EventData::None);
+        registry.handle(&Event::Error, EventData::None);
+
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let ready_tx = tx.clone();
+        let _ready = registry.register(Event::Ready, move |ctx| ready_tx.send(ctx).unwrap());
+        assert!(recv_context(&rx).is_some());
+
+        // Non-sticky events are not replayed
+        let error_tx = tx.clone();
+        let _error = registry.register(Event::Error, move |ctx| error_tx.send(ctx).unwrap());
+        assert!(recv_context(&rx).is_none());
+
+        registry.clear_sticky(&Event::Connected);
+        let _connected = registry.register(Event::Connected, move |ctx| tx.send(ctx).unwrap());
+        assert!(recv_context(&rx).is_none());
+    }
+
+    #[test]
+    fn only_replays_sticky_events_when_enabled() {
+        let registry = Arc::new(HandlerRegistry::new());
+        registry.handle(&Event::Ready, EventData::None);
+
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let ready_tx = tx.clone();
+        let _ready = registry.register(Event::Ready, move |ctx| ready_tx.send(ctx).unwrap());
+        assert!(recv_context(&rx).is_none());
+
+        let _sticky = registry.register_sticky(Event::Ready, move |ctx| tx.send(ctx).unwrap());
+        assert!(recv_context(&rx).is_some());
+    }
+
+    #[test]
+    fn once_handlers_are_called_once() {
+        let registry = Arc::new(HandlerRegistry::new());
+        registry.set_executor(HandlerExecutor::Inline);
+
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let _once = registry.register_with(
+            Event::Error,
+            move |_| tx.send(()).unwrap(),
+            HandlerOptions::new().once(),
+        );
+
+        registry.handle(&Event::Error, EventData::None);
+        registry.handle(&Event::Error, EventData::None);
+
+        assert_eq!(rx.try_iter().count(), 1);
+        assert!(registry.handlers.read()[&Event::Error].is_empty());
+    }
+
+    #[test]
+    fn handlers_run_by_priority_until_consumed() {
+        let registry = Arc::new(HandlerRegistry::new());
+        registry.set_executor(HandlerExecutor::Inline);
+
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let register = |priority: i32, consume: bool| {
+            let tx = tx.clone();
+            registry.register_with(
+                Event::ActivityJoinRequest,
+                move |ctx: Context| {
+                    tx.send(priority).unwrap();
+                    if consume {
+                        ctx.consume();
+                    }
+                },
+                HandlerOptions::new().priority(priority),
+            )
+        };
+
+        let _low = register(-1, false);
+        let _default = register(0, false);
+        let _high = register(10, false);
+
+        registry.handle(&Event::ActivityJoinRequest, EventData::None);
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), vec![10, 0, -1]);
+
+        // Handlers with the same priority still receive the consumed event
+        let consumer = register(5, true);
+        let _same = register(5, false);
+
+        registry.handle(&Event::ActivityJoinRequest, EventData::None);
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), vec![10, 5, 5]);
+
+        drop(consumer);
+        registry.handle(&Event::ActivityJoinRequest, EventData::None);
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), vec![10, 5, 0, -1]);
+    }
+
+    #[test]
+    fn wildcard_handlers_receive_every_event() {
+        let registry = Arc::new(HandlerRegistry::new());
+        registry.set_executor(HandlerExecutor::Inline);
+
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let any = registry.register_any(move |event, _| tx.send(event).unwrap());
+
+        registry.handle(&Event::Ready, EventData::None);
+        registry.handle(&Event::from("SOME_NEW_EVENT"), EventData::None);
+        assert_eq!(
+            rx.try_iter().collect::<Vec<_>>(),
+            vec![Event::Ready, Event::Other("SOME_NEW_EVENT".to_owned())]
+        );
+
+        drop(any);
+        assert!(registry.any.read().is_empty());
+
+        registry.handle(&Event::Ready, EventData::None);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn reference_counts_automatic_subscriptions() {
+        let registry = Arc::new(HandlerRegistry::new());
+
+        let join1 = registry.register(Event::ActivityJoin, |_| {});
+        let join2 = registry.register(Event::ActivityJoin, |_| {});
+        // Events that need args, or are not sent by Discord, are not subscribed to
+        let _message = registry.register(Event::MessageCreate, |_| {});
+        let _ready = registry.register(Event::Ready, |_| {});
+
+        assert_eq!(
+            registry.take_pending_subscriptions(),
+            vec![(Command::Subscribe, Event::ActivityJoin)]
+        );
+
+        drop(join1);
+        assert!(registry.take_pending_subscriptions().is_empty());
+        assert_eq!(registry.active_subscriptions(), vec![Event::ActivityJoin]);
+
+        drop(join2);
+        assert_eq!(
+            registry.take_pending_subscriptions(),
+            vec![(Command::Unsubscribe, Event::ActivityJoin)]
+        );
+        assert!(registry.active_subscriptions().is_empty());
+    }
+
+    #[test]
+    fn automatic_subscriptions_can_be_disabled() {
+        let registry = Arc::new(HandlerRegistry::new());
+        registry.set_auto_subscribe(false);
+
+        let _join = registry.register(Event::ActivityJoin, |_| {});
+        assert!(registry.take_pending_subscriptions().is_empty());
+    }
+
+    #[test]
+    fn counts_manual_subscriptions() {
+        let registry = Arc::new(HandlerRegistry::new());
+
+        // Sent by the client itself
+        assert!(registry.acquire_subscription(&Event::ActivityJoin));
+        let join = registry.register(Event::ActivityJoin, |_| {});
+        assert!(!registry.acquire_subscription(&Event::ActivityJoin));
+        assert!(registry.take_pending_subscriptions().is_empty());
+
+        // Held by the manual subscription
+        drop(join);
+        assert_eq!(
+            registry.release_subscription(&Event::ActivityJoin),
+            Some(false)
+        );
+        assert!(registry.take_pending_subscriptions().is_empty());
+        assert_eq!(registry.active_subscriptions(), vec![Event::ActivityJoin]);
+
+        assert_eq!(
+            registry.release_subscription(&Event::ActivityJoin),
+            Some(true)
+        );
+        assert!(registry.take_pending_subscriptions().is_empty());
+        assert_eq!(registry.release_subscription(&Event::ActivityJoin), None);
+
+        // The pending UNSUBSCRIBE of the last handler is replaced by the manual SUBSCRIBE
+        let join = registry.register(Event::ActivityJoin, |_| {});
+        registry.take_pending_subscriptions();
+        drop(join);
+        assert!(registry.acquire_subscription(&Event::ActivityJoin));
+        assert!(registry.take_pending_subscriptions().is_empty());
+    }
+
+    #[test]
+    fn reports_and_disables_panicking_handlers() {
+        let registry = Arc::new(HandlerRegistry::new());
+        registry.set_executor(HandlerExecutor::Inline);
+        registry.set_max_panics(Some(2));
+
+        let (tx, rx) = crossbeam_channel::unbounded();
+        registry.set_panic_handler(Arc::new(move |report| tx.send(report).unwrap()));
+
+        let bad = registry.register(Event::Error, |_| panic!("bad plugin"));
+        let _good = registry.register(Event::Error, |_| {});
+        // Kept alive, like a handler that is still running on another thread
+        let bad_registered = registry.handlers.read()[&Event::Error][0].clone();
+        assert_eq!(bad_registered.id, bad.id());
+
+        registry.handle(&Event::Error, EventData::None);
+        let report = rx.try_recv().unwrap();
+        assert_eq!(report.event, Event::Error);
+        assert_eq!(report.handler_id, bad.id());
+        assert_eq!(report.message, "bad plugin");
+        assert_eq!((report.panics, report.disabled), (1, false));
+
+        registry.handle(&Event::Error, EventData::None);
+        let report = rx.try_recv().unwrap();
+        assert_eq!((report.panics, report.disabled), (2, true));
+        assert_eq!(registry.handlers.read()[&Event::Error].len(), 1);
+
+        registry.handle(&Event::Error, EventData::None);
+        assert!(rx.try_recv().is_err());
+
+        // Dropping the handle of a disabled handler is not an error
+        assert!(matches!(bad.unregister(), Some(Ok(()))));
+        drop(bad_registered);
+    }
+
+    #[test]
+    fn contexts_carry_frame_metadata() {
+        let registry = Arc::new(HandlerRegistry::new());
+        registry.set_executor(HandlerExecutor::Inline);
+
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let _any = registry.register_any(move |_, ctx| tx.send(ctx).unwrap());
+
+        registry.handle(&Event::Disconnected, EventData::None);
+        let ctx = rx.try_recv().unwrap();
+        assert!(ctx.raw.is_none() && ctx.cmd.is_none());
+        assert_eq!(ctx.generation, 0);
+
+        registry.next_generation();
+        let raw = serde_json::json!({ "cmd": "DISPATCH", "evt": "SOME_NEW_EVENT", "nonce": "1" });
+        let frame = Frame {
+            raw: raw.clone(),
+            nonce: Some("1".to_owned()),
+            cmd: Command::Dispatch,
+            received_at: SystemTime::UNIX_EPOCH,
+        };
+        registry.handle_frame(&Event::from("SOME_NEW_EVENT"), EventData::None, Some(frame));
+
+        let ctx = rx.try_recv().unwrap();
+        assert_eq!(ctx.raw, Some(raw));
+        assert_eq!(ctx.nonce.as_deref(), Some("1"));
+        assert_eq!(ctx.cmd, Some(Command::Dispatch));
+        assert_eq!(ctx.received_at, SystemTime::UNIX_EPOCH);
+        assert_eq!(ctx.generation, 1);
+    }
 }
@@ -0,0 +1,274 @@
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Weak,
+    },
+    time::Duration,
+};
+
+use crossbeam_channel::{bounded, unbounded, Receiver, SendTimeoutError, Sender, TrySendError};
+
+use crate::{
+    event_handler::HandlerRegistry,
+    models::{Event, EventData},
+    DiscordError, Result,
+};
+
+/// What to do when a bounded [`EventReceiver`] is full
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum OverflowPolicy {
+    /// Drop the oldest queued event to make room for the new one
+    #[default]
+    DropOldest,
+    /// Wait until there is room for the new event
+    ///
+    /// This blocks the client's I/O thread, so commands and other events are delayed until the receiver catches up.
+    Block,
+}
+
+/// Configuration of an [`EventReceiver`], see [`Client::events_with`](crate::Client::events_with)
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct EventStreamConfig {
+    events: Option<HashSet<Event>>,
+    capacity: Option<usize>,
+    overflow: OverflowPolicy,
+}
+
+impl EventStreamConfig {
+    #[must_use]
+    /// Receive every event, without a limit on the number of queued events
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    /// Only receive the given event
+    ///
+    /// Can be called multiple times to receive several events. If never called, every event is received.
+    pub fn event(mut self, event: Event) -> Self {
+        self.events.get_or_insert_with(HashSet::new).insert(event);
+        self
+    }
+
+    #[must_use]
+    /// Only queue up to `capacity` events, applying `overflow` once the receiver is full
+    ///
+    /// A capacity of zero is treated as a capacity of one.
+    pub fn capacity(mut self, capacity: usize, overflow: OverflowPolicy) -> Self {
+        self.capacity = Some(capacity.max(1));
+        self.overflow = overflow;
+        self
+    }
+}
+
+/// The sending half of an [`EventReceiver`], kept by the registry
+pub(crate) struct EventSender {
+    events: Option<HashSet<Event>>,
+    overflow: OverflowPolicy,
+    tx: Sender<(Event, EventData)>,
+    // Used to drop the oldest event when the channel is full
+    rx: Receiver<(Event, EventData)>,
+    // Set once the receiver is dropped, as `rx` keeps the channel open
+    closed: AtomicBool,
+}
+
+impl EventSender {
+    pub fn send(&self, event: &Event, data: &EventData) {
+        if let Some(ref events) = self.events {
+            if !events.contains(event) {
+                return;
+            }
+        }
+
+        let mut message = (event.clone(), data.clone());
+
+        match self.overflow {
+            OverflowPolicy::Block => {
+                while !self.closed.load(Ordering::Relaxed) {
+                    match self.tx.send_timeout(message, Duration::from_millis(100)) {
+                        Err(SendTimeoutError::Timeout(returned)) => message = returned,
+                        _ => break,
+                    }
+                }
+            }
+            OverflowPolicy::DropOldest => {
+                while let Err(TrySendError::Full(returned)) = self.tx.try_send(message) {
+                    message = returned;
+                    if let Ok((dropped, _)) = self.rx.try_recv() {
+                        trace!("Event receiver is full, dropping {dropped:?} event");
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Receives events from the client, see [`Client::events`](crate::Client::events)
+///
+/// Events are queued in the order they are received from Discord,
+/// independently of the event handlers, which are still called as usual.
+///
+/// The receiver stops receiving events once it is dropped.
+/// Iterating over it blocks until the next event, and ends once the client is dropped.
+#[allow(clippy::module_name_repetitions)]
+pub struct EventReceiver {
+    rx: Receiver<(Event, EventData)>,
+    // Only the registry keeps the sender alive, so that the channel disconnects once the client is dropped
+    sender: Weak<EventSender>,
+    registry: Weak<HandlerRegistry>,
+}
+
+impl EventReceiver {
+    pub(crate) fn new(registry: &Arc<HandlerRegistry>, config: EventStreamConfig) -> Self {
+        let (tx, rx) = match config.capacity {
+            Some(capacity) => bounded(capacity),
+            None => unbounded(),
+        };
+
+        let sender = Arc::new(EventSender {
+            events: config.events,
+            overflow: config.overflow,
+            tx,
+            rx: rx.clone(),
+            closed: AtomicBool::new(false),
+        });
+        let weak_sender = Arc::downgrade(&sender);
+        registry.add_stream(sender);
+
+        Self {
+            rx,
+            sender: weak_sender,
+            registry: Arc::downgrade(registry),
+        }
+    }
+
+    /// Block until the next event is received
+    ///
+    /// # Errors
+    /// - The client was dropped
+    pub fn recv(&self) -> Result<(Event, EventData)> {
+        self.rx.recv().map_err(DiscordError::from)
+    }
+
+    /// Get the next event, if one is queued
+    #[must_use]
+    pub fn try_recv(&self) -> Option<(Event, EventData)> {
+        self.rx.try_recv().ok()
+    }
+
+    /// Block until the next event is received, or the timeout elapses
+    ///
+    /// Returns `None` if the timeout elapsed, or the client was dropped.
+    #[must_use]
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<(Event, EventData)> {
+        self.rx.recv_timeout(timeout).ok()
+    }
+
+    /// Iterate over the events that are currently queued, without blocking
+    pub fn try_iter(&self) -> impl Iterator<Item = (Event, EventData)> + '_ {
+        self.rx.try_iter()
+    }
+
+    /// The number of queued events
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.rx.len()
+    }
+
+    /// Whether no events are queued
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.rx.is_empty()
+    }
+}
+
+impl Iterator for EventReceiver {
+    type Item = (Event, EventData);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.rx.recv().ok()
+    }
+}
+
+impl Drop for EventReceiver {
+    fn drop(&mut self) {
+        if let Some(sender) = self.sender.upgrade() {
+            sender.closed.store(true, Ordering::Relaxed);
+
+            if let Some(registry) = self.registry.upgrade() {
+                registry.remove_stream(&sender);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn receives_filtered_events_in_order() {
+        let registry = Arc::new(HandlerRegistry::new());
+        let events = EventReceiver::new(
+            &registry,
+            EventStreamConfig::new()
+                .event(Event::Ready)
+                .event(Event::Connected),
+        );
+
+        registry.handle(&Event::Ready, EventData::None);
+        registry.handle(&Event::Error, EventData::None);
+        registry.handle(&Event::Connected, EventData::None);
+
+        let received: Vec<Event> = events.try_iter().map(|(event, _)| event).collect();
+        assert_eq!(received, vec![Event::Ready, Event::Connected]);
+    }
+
+    #[test]
+    fn drops_oldest_events_when_full() {
+        let registry = Arc::new(HandlerRegistry::new());
+        let events = EventReceiver::new(
+            &registry,
+            EventStreamConfig::new().capacity(2, OverflowPolicy::DropOldest),
+        );
+
+        registry.handle(&Event::Ready, EventData::None);
+        registry.handle(&Event::Connected, EventData::None);
+        registry.handle(&Event::Disconnected, EventData::None);
+
+        let received: Vec<Event> = events.try_iter().map(|(event, _)| event).collect();
+        assert_eq!(received, vec![Event::Connected, Event::Disconnected]);
+    }
+
+    #[test]
+    fn stops_receiving_once_dropped() {
+        let registry = Arc::new(HandlerRegistry::new());
+        let events = EventReceiver::new(&registry, EventStreamConfig::new());
+        drop(events);
+
+        // Would block forever if the dropped receiver was still registered
+        let blocking = EventReceiver::new(
+            &registry,
+            EventStreamConfig::new()
+                .event(Event::Error)
+                .capacity(1, OverflowPolicy::Block),
+        );
+        drop(blocking);
+
+        registry.handle(&Event::Error, EventData::None);
+        registry.handle(&Event::Error, EventData::None);
+    }
+
+    #[test]
+    fn ends_once_the_registry_is_dropped() {
+        let registry = Arc::new(HandlerRegistry::new());
+        let mut events = EventReceiver::new(&registry, EventStreamConfig::new());
+
+        registry.handle(&Event::Ready, EventData::None);
+        drop(registry);
+
+        assert!(matches!(events.next(), Some((Event::Ready, _))));
+        assert!(events.next().is_none());
+    }
+}
@@ -0,0 +1,188 @@
+use std::{fmt, sync::Arc, thread};
+
+use crossbeam_channel::{unbounded, Sender};
+
+/// A single call of an event handler
+pub type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// Executes jobs handed to it by a [`HandlerExecutor::Custom`] executor
+pub type CustomExecutor = dyn Fn(Job) + Send + Sync + 'static;
+
+/// How event handlers are run, see [`Client::with_handler_executor`](crate::Client::with_handler_executor)
+///
+/// Regardless of the executor, the handlers of a single event are handed over by priority, and then in the order they were registered,
+/// and events are handed over in the order they were received from Discord.
+/// Handlers of several priorities are handed over as a single job, see [`HandlerOptions::priority`](crate::event_handler::HandlerOptions::priority).
+/// The executor decides whether that order is kept while the handlers run.
+#[derive(Clone, Default)]
+#[allow(clippy::module_name_repetitions)]
+pub enum HandlerExecutor {
+    /// Spawn a new thread for every handler, for every event
+    ///
+    /// Handlers run concurrently, so no ordering is guaranteed.
+    #[default]
+    Spawn,
+    /// Run handlers on the thread that received the event, which is usually the client's I/O thread
+    ///
+    /// Handlers run one at a time, in order.
+    /// While a handler runs, no messages are sent or received,
+    /// so handlers must be quick, and must not wait for the response of a command, as that would deadlock.
+    Inline,
+    /// Run handlers on a single dispatcher thread, shared by all events
+    ///
+    /// Handlers run one at a time, in order, without blocking the client's I/O thread.
+    Ordered,
+    /// Run handlers on a fixed number of threads
+    ///
+    /// Handlers are started in order, but may run concurrently, and finish out of order.
+    /// A pool of zero threads is treated as a pool of one thread.
+    ThreadPool(usize),
+    /// Hand every handler call to a user supplied executor, such as an async runtime or a game engine's main loop
+    ///
+    /// The ordering guarantees are those of the executor.
+    Custom(Arc<CustomExecutor>),
+}
+
+impl HandlerExecutor {
+    /// Create a [`HandlerExecutor::Custom`] executor from a function that runs each job
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use discord_presence::{executor::HandlerExecutor, Client};
+    /// let (tx, rx) = std::sync::mpsc::channel();
+    /// let tx = std::sync::Mutex::new(tx);
+    ///
+    /// let mut drpc = Client::new(1003450375732482138)
+    ///     .with_handler_executor(HandlerExecutor::custom(move |job| {
+    ///         tx.lock().unwrap().send(job).unwrap();
+    ///     }));
+    /// drpc.start();
+    ///
+    /// // Run the handlers on the main thread
+    /// for job in rx {
+    ///     job();
+    /// }
+    /// ```
+    pub fn custom<F>(executor: F) -> Self
+    where
+        F: Fn(Job) + Send + Sync + 'static,
+    {
+        Self::Custom(Arc::new(executor))
+    }
+}
+
+impl fmt::Debug for HandlerExecutor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Spawn => write!(f, "Spawn"),
+            Self::Inline => write!(f, "Inline"),
+            Self::Ordered => write!(f, "Ordered"),
+            Self::ThreadPool(threads) => f.debug_tuple("ThreadPool").field(threads).finish(),
+            Self::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+/// A running [`HandlerExecutor`]
+///
+/// Worker threads stop once the runner is dropped, after finishing the queued jobs.
+pub(crate) enum Runner {
+    Spawn,
+    Inline,
+    Queue(Sender<Job>),
+    Custom(Arc<CustomExecutor>),
+}
+
+impl Runner {
+    pub fn new(executor: HandlerExecutor) -> Self {
+        match executor {
+            HandlerExecutor::Spawn => Self::Spawn,
+            HandlerExecutor::Inline => Self::Inline,
+            HandlerExecutor::Ordered => Self::queue(1),
+            HandlerExecutor::ThreadPool(threads) => Self::queue(threads.max(1)),
+            HandlerExecutor::Custom(executor) => Self::Custom(executor),
+        }
+    }
+
+    fn queue(threads: usize) -> Self {
+        let (tx, rx) = unbounded::<Job>();
+
+        for _ in 0..threads {
+            let rx = rx.clone();
+            thread::spawn(move || {
+                for job in rx {
+                    job();
+                }
+            });
+        }
+
+        Self::Queue(tx)
+    }
+
+    pub fn execute(&self, job: Job) {
+        match self {
+            Self::Spawn => {
+                thread::spawn(job);
+            }
+            Self::Inline => job(),
+            Self::Queue(tx) => {
+                if tx.send(job).is_err() {
+                    error!("Event handler threads have stopped, dropping event");
+                }
+            }
+            Self::Custom(executor) => executor(job),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn run_in_order(executor: HandlerExecutor) -> Vec<usize> {
+        let runner = Runner::new(executor);
+        let (tx, rx) = unbounded();
+
+        for i in 0..50 {
+            let tx = tx.clone();
+            runner.execute(Box::new(move || tx.send(i).unwrap()));
+        }
+
+        (0..50)
+            .map(|_| rx.recv_timeout(Duration::from_secs(5)).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn ordered_executors_keep_order() {
+        let expected: Vec<usize> = (0..50).collect();
+
+        assert_eq!(run_in_order(HandlerExecutor::Inline), expected);
+        assert_eq!(run_in_order(HandlerExecutor::Ordered), expected);
+        assert_eq!(run_in_order(HandlerExecutor::ThreadPool(1)), expected);
+    }
+
+    #[test]
+    fn thread_pool_runs_every_job() {
+        let mut ran = run_in_order(HandlerExecutor::ThreadPool(4));
+        ran.sort_unstable();
+
+        assert_eq!(ran, (0..50).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn custom_executor_receives_jobs() {
+        let (tx, rx) = unbounded::<Job>();
+        let runner = Runner::new(HandlerExecutor::custom(move |job| tx.send(job).unwrap()));
+
+        let (done_tx, done_rx) = unbounded();
+        runner.execute(Box::new(move || done_tx.send(()).unwrap()));
+
+        // Nothing runs until the custom executor runs the job
+        assert!(done_rx.try_recv().is_err());
+        rx.recv().unwrap()();
+        assert!(done_rx.try_recv().is_ok());
+    }
+}
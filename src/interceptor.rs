@@ -0,0 +1,163 @@
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+
+use crate::models::payload::Payload;
+
+/// What to do with an intercepted payload, see [`Interceptor`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Action {
+    /// Hand the payload, including any changes made to it, to the next interceptor
+    #[default]
+    Pass,
+    /// Drop the payload, skipping the remaining interceptors
+    Drop,
+}
+
+/// Sees the payloads exchanged with Discord, see [`Client::with_interceptor`](crate::Client::with_interceptor)
+///
+/// Interceptors run in the order they were added, on the client's I/O thread for inbound payloads,
+/// and on the thread sending the command for outbound payloads, so they must be quick.
+/// Each interceptor can pass a payload through, modify it, or drop it.
+///
+/// - A dropped command fails with [`DiscordError::Intercepted`](crate::DiscordError::Intercepted).
+/// - A dropped command response makes the command fail with [`DiscordError::Intercepted`](crate::DiscordError::Intercepted).
+/// - A dropped event is not dispatched to the event handlers.
+/// - A modified event is dispatched with the modified data, while [`Context::raw`](crate::event_handler::Context::raw) keeps the payload as Discord sent it.
+///
+/// The handshake, and the READY event it is answered with, are not intercepted.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use discord_presence::{interceptor::{Action, Interceptor}, models::{payload::Payload, Command}, Client};
+/// struct Kiosk;
+///
+/// impl Interceptor for Kiosk {
+///     fn outbound(&self, payload: &mut Payload) -> Action {
+///         if payload.cmd == Command::SendActivityJoinInvite {
+///             Action::Drop
+///         } else {
+///             Action::Pass
+///         }
+///     }
+/// }
+///
+/// let mut drpc = Client::new(1003450375732482138).with_interceptor(Kiosk);
+/// drpc.start();
+/// ```
+pub trait Interceptor: Send + Sync {
+    /// Called with every command before it is encoded and sent
+    fn outbound(&self, payload: &mut Payload) -> Action {
+        let _ = payload;
+        Action::Pass
+    }
+
+    /// Called with every received command response and event, before it is returned to the caller or dispatched
+    fn inbound(&self, payload: &mut Payload) -> Action {
+        let _ = payload;
+        Action::Pass
+    }
+}
+
+/// The interceptors of a client, shared by all of its clones
+#[derive(Default)]
+pub(crate) struct InterceptorChain {
+    interceptors: RwLock<Vec<Arc<dyn Interceptor>>>,
+}
+
+impl InterceptorChain {
+    pub fn add(&self, interceptor: Arc<dyn Interceptor>) {
+        self.interceptors.write().push(interceptor);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.interceptors.read().is_empty()
+    }
+
+    pub fn outbound(&self, payload: &mut Payload) -> Action {
+        self.run(payload, |interceptor, payload| {
+            interceptor.outbound(payload)
+        })
+    }
+
+    pub fn inbound(&self, payload: &mut Payload) -> Action {
+        self.run(payload, |interceptor, payload| interceptor.inbound(payload))
+    }
+
+    fn run<F>(&self, payload: &mut Payload, f: F) -> Action
+    where
+        F: Fn(&dyn Interceptor, &mut Payload) -> Action,
+    {
+        // Cloned, so that interceptors can be added from within an interceptor
+        let interceptors = self.interceptors.read().clone();
+
+        for interceptor in interceptors {
+            if f(interceptor.as_ref(), payload) == Action::Drop {
+                return Action::Drop;
+            }
+        }
+
+        Action::Pass
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Command, Event};
+    use serde_json::json;
+
+    struct Rename;
+
+    impl Interceptor for Rename {
+        fn outbound(&self, payload: &mut Payload) -> Action {
+            payload.args = Some(json!({ "name": "renamed" }));
+            Action::Pass
+        }
+    }
+
+    struct DropErrors;
+
+    impl Interceptor for DropErrors {
+        fn inbound(&self, payload: &mut Payload) -> Action {
+            if payload.evt == Some(Event::Error) {
+                Action::Drop
+            } else {
+                Action::Pass
+            }
+        }
+
+        fn outbound(&self, payload: &mut Payload) -> Action {
+            // Sees the changes of the previous interceptors
+            assert_eq!(payload.args, Some(json!({ "name": "renamed" })));
+            Action::Pass
+        }
+    }
+
+    #[test]
+    fn runs_interceptors_in_order() {
+        let chain = InterceptorChain::default();
+        chain.add(Arc::new(Rename));
+        chain.add(Arc::new(DropErrors));
+
+        let mut payload = Payload::with_nonce(Command::SetActivity, None, None, None);
+        assert_eq!(chain.outbound(&mut payload), Action::Pass);
+        assert_eq!(payload.args, Some(json!({ "name": "renamed" })));
+    }
+
+    #[test]
+    fn stops_at_the_first_drop() {
+        let chain = InterceptorChain::default();
+        assert!(chain.is_empty());
+
+        chain.add(Arc::new(DropErrors));
+        chain.add(Arc::new(Rename));
+
+        let mut error = Payload::with_nonce(Command::Dispatch, None, None, Some(Event::Error));
+        assert_eq!(chain.inbound(&mut error), Action::Drop);
+
+        let mut ready = Payload::with_nonce(Command::Dispatch, None, None, Some(Event::Ready));
+        assert_eq!(chain.inbound(&mut ready), Action::Pass);
+    }
+}
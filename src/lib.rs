@@ -28,8 +28,18 @@ mod connection;
 pub mod error;
 /// Event handlers
 pub mod event_handler;
+/// Pull-style event consumption
+pub mod event_stream;
+/// Executors that run event handlers
+pub mod executor;
+/// Interceptors that see the payloads exchanged with Discord
+pub mod interceptor;
 /// Models for discord activity
 pub mod models;
+/// Recording and replaying sessions
+pub mod session;
+/// Connection state tracking
+pub mod state;
 mod utils;
 
 use std::sync::atomic::AtomicBool;
@@ -37,3 +47,4 @@ use std::sync::atomic::AtomicBool;
 pub use client::Client;
 pub use error::{DiscordError, Result};
 pub use models::Event;
+pub use state::ConnectionState;
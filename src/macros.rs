@@ -79,38 +79,37 @@ macro_rules! into_error {
 }
 
 macro_rules! builder {
-    [ @st ( $name:ident $field:tt: $type:tt alias = $alias:tt $(=> if feature = $feature:tt)?, $($rest:tt)* ) -> ( $($out:tt)* ) ] => {
-        builder![ @st
+    [ @st [ $($derive:tt)* ] ( $name:ident $field:tt: $type:tt alias = $alias:tt $(=> if feature = $feature:tt)?, $($rest:tt)* ) -> ( $($out:tt)* ) ] => {
+        builder![ @st [ $($derive)* ]
             ( $name $($rest)* ) -> (
                 $($out)*
                 $(#[cfg(feature = $feature)])?
                 #[doc = concat!("Optional " , stringify!($field), " field")]
                 #[serde(skip_serializing_if = "Option::is_none", rename = $alias)]
+                #[allow(clippy::pub_underscore_fields)]
                 pub $field: Option<$type>,
             )
         ];
     };
 
-    [ @st ( $name:ident $field:tt: $type:tt func $(=> if feature = $feature:tt)?, $($rest:tt)* ) -> ( $($out:tt)* ) ] => {
-        builder![ @st ( $name $field: $type, $($rest)* ) -> ( $($out)* ) ];
+    [ @st [ $($derive:tt)* ] ( $name:ident $field:tt: $type:tt func $(=> if feature = $feature:tt)?, $($rest:tt)* ) -> ( $($out:tt)* ) ] => {
+        builder![ @st [ $($derive)* ] ( $name $field: $type, $($rest)* ) -> ( $($out)* ) ];
     };
 
-    // TODO: Make this more applicable for other types than just buttons
-    // Currently the implementation here only works for buttons, thanks to the deserialize_with attribute
-    [ @st ( $name:ident $field:ident: $type:ty as array, $($rest:tt)* ) -> ( $($out:tt)* ) ] => {
-        builder![ @st
+    [ @st [ $($derive:tt)* ] ( $name:ident $field:ident: $type:ty as array $(deserialize_with = $deser:literal)?, $($rest:tt)* ) -> ( $($out:tt)* ) ] => {
+        builder![ @st [ $($derive)* ]
             ( $name $($rest)* ) -> (
                 $($out)*
                 #[doc = concat!("Optional ", stringify!($field), " field")]
-                #[serde(default, skip_serializing_if = "Vec::is_empty", deserialize_with = "serialize_activity_button")]
+                #[serde(default, skip_serializing_if = "Vec::is_empty" $(, deserialize_with = $deser)?)]
                 pub $field: Vec<$type>,
             )
         ];
     };
 
 
-    [ @st ( $name:ident $field:ident: $type:ty $(=> if feature = $feature:tt)?, $($rest:tt)* ) -> ( $($out:tt)* ) ] => {
-        builder![ @st
+    [ @st [ $($derive:tt)* ] ( $name:ident $field:ident: $type:ty $(=> if feature = $feature:tt)?, $($rest:tt)* ) -> ( $($out:tt)* ) ] => {
+        builder![ @st [ $($derive)* ]
             ( $name $($rest)* ) -> (
                 $($out)*
                 #[doc = concat!("Optional " , stringify!($field), " field")]
@@ -120,9 +119,9 @@ macro_rules! builder {
         ];
     };
 
-    [ @st ( $name:ident ) -> ( $($out:tt)* ) ] => {
+    [ @st [ $($derive:tt)* ] ( $name:ident ) -> ( $($out:tt)* ) ] => {
         #[doc = concat!(stringify!($name), " struct")]
-        #[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize, Hash, Eq)]
+        #[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize, $($derive)*)]
         pub struct $name { $($out)* }
     };
 
@@ -130,7 +129,7 @@ macro_rules! builder {
         builder![ @im ( $name $($rest)* ) -> ( builder_func![$field, $type func $(=> if feature = $feature)?]; $($out)* ) ];
     };
 
-    [ @im ( $name:ident $field:ident: $type:tt as array $(=> if feature = $feature:tt)?, $($rest:tt)* ) -> ( $($out:tt)* ) ] => {
+    [ @im ( $name:ident $field:ident: $type:tt as array $(deserialize_with = $deser:literal)? $(=> if feature = $feature:tt)?, $($rest:tt)* ) -> ( $($out:tt)* ) ] => {
         builder![ @im ( $name $($rest)* ) -> ( builder_array![$field, $type array]; $($out)* ) ];
     };
 
@@ -154,8 +153,14 @@ macro_rules! builder {
         }
     };
 
+    // Structs containing floating point fields cannot derive `Eq` or `Hash`
+    [ partial_eq $name:ident $($body:tt)* ] => {
+        builder![@st [] ( $name $($body)* ) -> () ];
+        builder![@im ( $name $($body)* ) -> () ];
+    };
+
     [ $name:ident $($body:tt)* ] => {
-        builder![@st ( $name $($body)* ) -> () ];
+        builder![@st [Hash, Eq] ( $name $($body)* ) -> () ];
         builder![@im ( $name $($body)* ) -> () ];
     }
 }
@@ -0,0 +1,132 @@
+use super::events::PartialUser;
+
+builder! {MessageEvent
+    channel_id: String,
+    message:    Message func,
+}
+
+builder! {NotificationCreateEvent
+    channel_id: String,
+    message:    Message func,
+    icon_url:   String,
+    title:      String,
+    body:       String,
+}
+
+builder! {Message
+    id:               String,
+    blocked:          bool,
+    bot:              bool,
+    content:          String,
+    nick:             String,
+    author_color:     String,
+    edited_timestamp: String,
+    timestamp:        String,
+    tts:              bool,
+    mentions:         PartialUser as array,
+    mention_roles:    String as array,
+    mention_everyone: bool,
+    embeds:           Embed as array,
+    attachments:      MessageAttachment as array,
+    author:           PartialUser func,
+    pinned:           bool,
+    _type:            u32 alias = "type",
+}
+
+builder! {MessageAttachment
+    id:        String,
+    filename:  String,
+    size:      u64,
+    url:       String,
+    proxy_url: String,
+    height:    u32,
+    width:     u32,
+}
+
+builder! {Embed
+    title:       String,
+    _type:       String alias = "type",
+    description: String,
+    url:         String,
+    timestamp:   String,
+    color:       u32,
+    footer:      EmbedFooter func,
+    image:       EmbedMedia func,
+    thumbnail:   EmbedMedia func,
+    video:       EmbedMedia func,
+    provider:    EmbedProvider func,
+    author:      EmbedAuthor func,
+    fields:      EmbedField as array,
+}
+
+builder! {EmbedFooter
+    text:           String,
+    icon_url:       String,
+    proxy_icon_url: String,
+}
+
+builder! {EmbedMedia
+    url:       String,
+    proxy_url: String,
+    height:    u32,
+    width:     u32,
+}
+
+builder! {EmbedProvider
+    name: String,
+    url:  String,
+}
+
+builder! {EmbedAuthor
+    name:           String,
+    url:            String,
+    icon_url:       String,
+    proxy_icon_url: String,
+}
+
+builder! {EmbedField
+    name:   String,
+    value:  String,
+    inline: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_deserialize_message_event() {
+        let json = include_str!("../../tests/fixtures/message_create.json");
+        let parsed = serde_json::from_str::<MessageEvent>(json).unwrap();
+
+        let event = MessageEvent::new()
+            .channel_id("199737254929760256")
+            .message(|m| {
+                m.id("199743874640379904")
+                    .blocked(false)
+                    .bot(false)
+                    .content("test")
+                    .nick("test name")
+                    .author_color("#ffffff")
+                    .timestamp("2016-07-05T04:30:50.776Z")
+                    .tts(false)
+                    .mention_everyone(false)
+                    .append_embeds(|e| {
+                        e.title("Ferris")
+                            .color(0x00de_a584)
+                            .append_fields(|f| f.name("Crab").value("Yes").inline(true))
+                    })
+                    .append_attachments(|a| a.id("1").filename("ferris.png").size(1024))
+                    .author(|a| {
+                        a.id("190320984123768832")
+                            .username("test 2")
+                            .discriminator("7479")
+                            .bot(false)
+                    })
+                    .pinned(false)
+                    ._type(0)
+            });
+
+        assert_eq!(parsed, event);
+    }
+}
@@ -1,10 +1,361 @@
-use super::events::PartialUser;
+use serde::{
+    de::{DeserializeOwned, IgnoredAny},
+    Serialize,
+};
 
-builder! {SubscriptionArgs
-    secret: String,     // Activity{Join,Spectate}
-    user: PartialUser,  // ActivityJoinRequest
+use super::{
+    rich_presence::{
+        Activity, CloseActivityRequestArgs, SendActivityJoinInviteArgs, SetActivityArgs,
+    },
+    voice_settings::{UserVoiceSettings, VoiceSettings},
+    Command, Event,
+};
+use crate::Result;
+
+/// Args to subscribe to, or unsubscribe from, an event
+///
+/// Which variant is required depends on the event, see the docs of each [`Event`](super::Event) variant.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum SubscriptionArgs {
+    /// Args for events scoped to a voice or text channel
+    Channel(ChannelSubscriptionArgs),
+    /// Args for events scoped to a guild
+    Guild(GuildSubscriptionArgs),
+    /// The event does not take any args
+    None {},
+}
+
+impl SubscriptionArgs {
+    #[must_use]
+    /// Create [`SubscriptionArgs`] for an event scoped to the given channel
+    pub fn channel(channel_id: u64) -> Self {
+        Self::Channel(ChannelSubscriptionArgs::new(channel_id))
+    }
+
+    #[must_use]
+    /// Create [`SubscriptionArgs`] for an event scoped to the given guild
+    pub fn guild(guild_id: u64) -> Self {
+        Self::Guild(GuildSubscriptionArgs::new(guild_id))
+    }
+}
+
+impl Default for SubscriptionArgs {
+    fn default() -> Self {
+        Self::None {}
+    }
+}
+
+impl From<ChannelSubscriptionArgs> for SubscriptionArgs {
+    fn from(args: ChannelSubscriptionArgs) -> Self {
+        Self::Channel(args)
+    }
+}
+
+impl From<GuildSubscriptionArgs> for SubscriptionArgs {
+    fn from(args: GuildSubscriptionArgs) -> Self {
+        Self::Guild(args)
+    }
+}
+
+/// Args to subscribe to an event scoped to a channel
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct ChannelSubscriptionArgs {
+    /// The id of the channel
+    pub channel_id: String,
+}
+
+impl ChannelSubscriptionArgs {
+    #[must_use]
+    /// Create a new `ChannelSubscriptionArgs`
+    pub fn new(channel_id: u64) -> Self {
+        Self {
+            channel_id: channel_id.to_string(),
+        }
+    }
+}
+
+/// Args to subscribe to an event scoped to a guild
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct GuildSubscriptionArgs {
+    /// The id of the guild
+    pub guild_id: String,
+}
+
+impl GuildSubscriptionArgs {
+    #[must_use]
+    /// Create a new `GuildSubscriptionArgs`
+    pub fn new(guild_id: u64) -> Self {
+        Self {
+            guild_id: guild_id.to_string(),
+        }
+    }
 }
 
 builder! {Subscription
     evt: String,
 }
+
+/// Args to authorize the application with the user's Discord account, see [`Authorize`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct AuthorizeArgs {
+    /// The id of the application
+    pub client_id: String,
+    /// The OAuth scopes to request
+    pub scopes: Vec<String>,
+    /// A one-time use RPC token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rpc_token: Option<String>,
+    /// The username to create a guest account with, if the user has no Discord account
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+}
+
+impl AuthorizeArgs {
+    #[must_use]
+    /// Create a new `AuthorizeArgs`
+    pub fn new<I, S>(client_id: u64, scopes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            client_id: client_id.to_string(),
+            scopes: scopes.into_iter().map(Into::into).collect(),
+            rpc_token: None,
+            username: None,
+        }
+    }
+}
+
+builder! {AuthorizeResponse
+    code: String,
+}
+
+/// Args for commands that do not take any args
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct EmptyArgs {}
+
+/// Args to subscribe to, or unsubscribe from, an event
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct SubscribeArgs {
+    /// The event to subscribe to
+    #[serde(skip)]
+    pub event: Event,
+    /// The args of the event
+    #[serde(flatten)]
+    pub args: SubscriptionArgs,
+}
+
+impl SubscribeArgs {
+    /// Create a new `SubscribeArgs`
+    pub fn new<A>(event: Event, args: A) -> Self
+    where
+        A: Into<SubscriptionArgs>,
+    {
+        Self {
+            event,
+            args: args.into(),
+        }
+    }
+}
+
+/// A command that can be sent to Discord, tied to the type of its args and response
+///
+/// This is implemented for every command supported by this crate,
+/// except [`Command::Dispatch`], which Discord uses to send events and is never sent by the client.
+/// It can be implemented by other crates to add support for more commands.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use discord_presence::{models::{Command, RpcCommand}, Client};
+/// # use serde_json::{json, Value};
+/// struct GetChannel;
+///
+/// impl RpcCommand for GetChannel {
+///     type Args = Value;
+///     type Response = Value;
+///
+///     fn command() -> Command {
+///         Command::from("GET_CHANNEL")
+///     }
+/// }
+///
+/// # let mut drpc = Client::new(1003450375732482138);
+/// let channel = drpc.send::<GetChannel>(json!({ "channel_id": "1234" })).unwrap();
+/// ```
+pub trait RpcCommand {
+    /// The args sent with the command
+    type Args: Serialize + Send + Sync;
+    /// The data Discord responds with
+    type Response: DeserializeOwned;
+
+    /// The command to send
+    fn command() -> Command;
+
+    /// The event the command refers to, if any
+    fn event(_args: &Self::Args) -> Option<Event> {
+        None
+    }
+
+    /// Check that the args are valid before they are sent
+    ///
+    /// # Errors
+    /// - The args are invalid
+    fn validate(_args: &Self::Args) -> Result<()> {
+        Ok(())
+    }
+}
+
+macro_rules! rpc_command {
+    [ $(#[$meta:meta])* $name:ident, $args:ty => $response:ty ] => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub struct $name;
+
+        impl RpcCommand for $name {
+            type Args = $args;
+            type Response = $response;
+
+            fn command() -> Command {
+                Command::$name
+            }
+        }
+    };
+}
+
+rpc_command![
+    /// Authorize the application with the user's Discord account, returning an OAuth authorization code
+    ///
+    /// Discord asks the user to confirm, so the response only arrives once they have answered.
+    Authorize, AuthorizeArgs => AuthorizeResponse
+];
+
+rpc_command![
+    /// Set the user's current activity, or clear it if no activity is given
+    SetActivity, SetActivityArgs => Option<Activity>
+];
+
+// NOTE: The responses of SEND_ACTIVITY_JOIN_INVITE and CLOSE_ACTIVITY_REQUEST are not documented
+rpc_command![
+    /// Send an invite to a user to join a game
+    SendActivityJoinInvite, SendActivityJoinInviteArgs => IgnoredAny
+];
+
+rpc_command![
+    /// Close a request to join a game
+    CloseActivityRequest, CloseActivityRequestArgs => IgnoredAny
+];
+
+rpc_command![
+    /// Get the user's voice settings
+    GetVoiceSettings, EmptyArgs => VoiceSettings
+];
+
+/// Set the user's voice settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SetVoiceSettings;
+
+impl RpcCommand for SetVoiceSettings {
+    type Args = VoiceSettings;
+    type Response = VoiceSettings;
+
+    fn command() -> Command {
+        Command::SetVoiceSettings
+    }
+
+    fn validate(args: &Self::Args) -> Result<()> {
+        args.validate()
+    }
+}
+
+/// Set the voice settings of another user, local to the current user
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SetUserVoiceSettings;
+
+impl RpcCommand for SetUserVoiceSettings {
+    type Args = UserVoiceSettings;
+    type Response = UserVoiceSettings;
+
+    fn command() -> Command {
+        Command::SetUserVoiceSettings
+    }
+
+    fn validate(args: &Self::Args) -> Result<()> {
+        args.validate()
+    }
+}
+
+/// Subscribe to an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Subscribe;
+
+impl RpcCommand for Subscribe {
+    type Args = SubscribeArgs;
+    type Response = Subscription;
+
+    fn command() -> Command {
+        Command::Subscribe
+    }
+
+    fn event(args: &Self::Args) -> Option<Event> {
+        Some(args.event.clone())
+    }
+}
+
+/// Unsubscribe from an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Unsubscribe;
+
+impl RpcCommand for Unsubscribe {
+    type Args = SubscribeArgs;
+    type Response = Subscription;
+
+    fn command() -> Command {
+        Command::Unsubscribe
+    }
+
+    fn event(args: &Self::Args) -> Option<Event> {
+        Some(args.event.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_serialize_subscription_args() {
+        let none = serde_json::to_string(&SubscriptionArgs::default()).unwrap();
+        assert_eq![none, "{}"];
+
+        let channel = serde_json::to_string(&SubscriptionArgs::channel(1234)).unwrap();
+        assert_eq![channel, r#"{"channel_id":"1234"}"#];
+
+        let guild = serde_json::to_string(&SubscriptionArgs::guild(1234)).unwrap();
+        assert_eq![guild, r#"{"guild_id":"1234"}"#];
+    }
+
+    #[test]
+    fn can_serialize_authorize_args() {
+        let args = AuthorizeArgs::new(1234, ["rpc", "identify"]);
+        assert_eq!(Authorize::command(), Command::Authorize);
+
+        let json = serde_json::to_string(&args).unwrap();
+        assert_eq![json, r#"{"client_id":"1234","scopes":["rpc","identify"]}"#];
+    }
+
+    #[test]
+    fn can_serialize_subscribe_args() {
+        let args = SubscribeArgs::new(Event::SpeakingStart, SubscriptionArgs::channel(1234));
+        assert_eq!(Subscribe::event(&args), Some(Event::SpeakingStart));
+
+        let json = serde_json::to_string(&args).unwrap();
+        assert_eq![json, r#"{"channel_id":"1234"}"#];
+
+        let args = SubscribeArgs::new(Event::ActivityJoin, SubscriptionArgs::default());
+        let json = serde_json::to_string(&args).unwrap();
+        assert_eq![json, "{}"];
+    }
+}
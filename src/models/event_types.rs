@@ -0,0 +1,217 @@
+use super::{
+    chat::{MessageEvent, NotificationCreateEvent},
+    events::{
+        ConnectionAttemptEvent, ConnectionFailedEvent, ErrorEvent, ReadyEvent, SpeakingEvent,
+        VoiceConnectionStatusEvent, VoiceStateEvent,
+    },
+    guild::{ChannelCreateEvent, GuildCreateEvent, GuildStatusEvent},
+    rich_presence::{ActivityJoinEvent, ActivityJoinRequestEvent, ActivitySpectateEvent},
+    voice_settings::VoiceSettings,
+    Event, EventData,
+};
+
+/// An event, tied to the type of its data
+///
+/// Every natively supported [`Event`] has a marker type in this module, named after its variant,
+/// which can be passed to [`Client::on`](crate::Client::on) to receive the event data already destructured.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use discord_presence::{models::event_types, Client};
+/// let mut drpc = Client::new(1003450375732482138);
+///
+/// drpc.on::<event_types::Ready, _>(|ready, _ctx| {
+///     println!("Logged in as {:?}", ready.user);
+/// })
+/// .persist();
+/// ```
+pub trait RpcEvent {
+    /// The data the event is received with
+    type Data;
+
+    /// The event to listen for
+    fn event() -> Event;
+
+    /// Extract the typed data from the event data
+    ///
+    /// Returns `None` if the data does not belong to this event, e.g. if Discord sent data that failed to parse.
+    fn data(data: EventData) -> Option<Self::Data>;
+}
+
+macro_rules! rpc_event {
+    [ $(#[$meta:meta])* $name:ident => $data:ty ] => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub struct $name;
+
+        impl RpcEvent for $name {
+            type Data = $data;
+
+            fn event() -> Event {
+                Event::$name
+            }
+
+            fn data(data: EventData) -> Option<Self::Data> {
+                match data {
+                    EventData::$name(data) => Some(data),
+                    _ => None,
+                }
+            }
+        }
+    };
+
+    [ $(#[$meta:meta])* $name:ident ] => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub struct $name;
+
+        impl RpcEvent for $name {
+            type Data = ();
+
+            fn event() -> Event {
+                Event::$name
+            }
+
+            fn data(data: EventData) -> Option<Self::Data> {
+                match data {
+                    EventData::None => Some(()),
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+rpc_event![
+    /// The client completed the handshake for the first time
+    Ready => ReadyEvent
+];
+
+rpc_event![
+    /// The client connected to Discord
+    Connected
+];
+
+rpc_event![
+    /// The client lost its connection to Discord
+    Disconnected
+];
+
+rpc_event![
+    /// The client failed to connect to Discord
+    Error => ErrorEvent
+];
+
+rpc_event![
+    /// The client is about to retry connecting to Discord
+    ConnectionAttempt => ConnectionAttemptEvent
+];
+
+rpc_event![
+    /// The client gave up connecting to Discord
+    ConnectionFailed => ConnectionFailedEvent
+];
+
+rpc_event![
+    /// The user joined a game
+    ActivityJoin => ActivityJoinEvent
+];
+
+rpc_event![
+    /// The user spectated a game
+    ActivitySpectate => ActivitySpectateEvent
+];
+
+rpc_event![
+    /// Another user requested to join the user's game
+    ActivityJoinRequest => ActivityJoinRequestEvent
+];
+
+rpc_event![
+    /// A user joined the subscribed voice channel
+    VoiceStateCreate => VoiceStateEvent
+];
+
+rpc_event![
+    /// A user's voice state changed in the subscribed voice channel
+    VoiceStateUpdate => VoiceStateEvent
+];
+
+rpc_event![
+    /// A user left the subscribed voice channel
+    VoiceStateDelete => VoiceStateEvent
+];
+
+rpc_event![
+    /// The user's voice settings changed
+    VoiceSettingsUpdate => VoiceSettings
+];
+
+rpc_event![
+    /// The user's voice connection status changed
+    VoiceConnectionStatus => VoiceConnectionStatusEvent
+];
+
+rpc_event![
+    /// A user started speaking in the subscribed voice channel
+    SpeakingStart => SpeakingEvent
+];
+
+rpc_event![
+    /// A user stopped speaking in the subscribed voice channel
+    SpeakingStop => SpeakingEvent
+];
+
+rpc_event![
+    /// A message was sent in the subscribed text channel
+    MessageCreate => MessageEvent
+];
+
+rpc_event![
+    /// A message was edited in the subscribed text channel
+    MessageUpdate => MessageEvent
+];
+
+rpc_event![
+    /// A message was deleted in the subscribed text channel
+    MessageDelete => MessageEvent
+];
+
+rpc_event![
+    /// The user received a notification
+    NotificationCreate => NotificationCreateEvent
+];
+
+rpc_event![
+    /// The status of the subscribed guild changed
+    GuildStatus => GuildStatusEvent
+];
+
+rpc_event![
+    /// The user joined a guild
+    GuildCreate => GuildCreateEvent
+];
+
+rpc_event![
+    /// The user joined a channel
+    ChannelCreate => ChannelCreateEvent
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_typed_data() {
+        assert_eq!(Ready::event(), Event::Ready);
+        assert!(Ready::data(EventData::Ready(ReadyEvent::default())).is_some());
+        assert!(Ready::data(EventData::None).is_none());
+
+        assert_eq!(Connected::data(EventData::None), Some(()));
+        assert!(
+            VoiceStateDelete::data(EventData::VoiceStateUpdate(VoiceStateEvent::default()))
+                .is_none()
+        );
+    }
+}
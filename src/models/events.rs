@@ -1,3 +1,7 @@
+use std::time::Duration;
+
+use super::voice_settings::Pan;
+
 builder! {ReadyEvent
     v:      u32,
     config: RpcServerConfiguration,
@@ -9,6 +13,17 @@ builder! {ErrorEvent
     message: String,
 }
 
+builder! {ConnectionAttemptEvent
+    attempt:    usize,
+    next_delay: Duration,
+    error:      String,
+}
+
+builder! {ConnectionFailedEvent
+    attempts: usize,
+    error:    String,
+}
+
 builder! {RpcServerConfiguration
     cdn_host: String,
     api_endpoint: String,
@@ -20,4 +35,60 @@ builder! {PartialUser
     username:      String,
     discriminator: String,
     avatar:        String,
+    bot:           bool,
+}
+
+builder! {partial_eq VoiceStateEvent
+    voice_state: VoiceState func,
+    user:        PartialUser func,
+    nick:        String,
+    volume:      f32,
+    mute:        bool,
+    pan:         Pan func,
+}
+
+builder! {VoiceState
+    mute:      bool,
+    deaf:      bool,
+    self_mute: bool,
+    self_deaf: bool,
+    suppress:  bool,
+}
+
+/// The state of the voice connection
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum VoiceConnectionState {
+    /// TCP disconnected
+    Disconnected,
+    /// Waiting for voice endpoint
+    AwaitingEndpoint,
+    /// TCP authenticating
+    Authenticating,
+    /// TCP connecting
+    Connecting,
+    /// TCP connected
+    Connected,
+    /// TCP connected, Voice disconnected
+    VoiceDisconnected,
+    /// TCP connected, Voice connecting
+    VoiceConnecting,
+    /// TCP connected, Voice connected
+    VoiceConnected,
+    /// No route to host
+    NoRoute,
+    /// WebRTC ice checking
+    IceChecking,
+}
+
+builder! {VoiceConnectionStatusEvent
+    state:        VoiceConnectionState,
+    hostname:     String,
+    pings:        u32 as array,
+    average_ping: u32,
+    last_ping:    u32,
+}
+
+builder! {SpeakingEvent
+    user_id: String,
 }
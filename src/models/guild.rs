@@ -0,0 +1,21 @@
+builder! {GuildStatusEvent
+    guild:  PartialGuild func,
+    online: u32,
+}
+
+builder! {PartialGuild
+    id:       String,
+    name:     String,
+    icon_url: String,
+}
+
+builder! {GuildCreateEvent
+    id:   String,
+    name: String,
+}
+
+builder! {ChannelCreateEvent
+    id:    String,
+    name:  String,
+    _type: u32 alias = "type",
+}
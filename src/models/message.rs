@@ -10,7 +10,8 @@ pub(crate) const MAX_RPC_MESSAGE_SIZE: usize =
     MAX_RPC_FRAME_SIZE - std::mem::size_of::<FrameHeader>();
 
 /// Codes for payload types
-#[derive(Debug, Copy, Clone, PartialEq, Eq, FromPrimitive)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, FromPrimitive, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 #[repr(u32)]
 pub enum OpCode {
     /// Handshake payload
@@ -1,18 +1,26 @@
+/// The chat messages module
+pub mod chat;
 /// The Discord commands module
 pub mod commands;
+/// The typed events module
+pub mod event_types;
 /// The events module
 pub mod events;
+/// The guilds and channels module
+pub mod guild;
 /// The module to handle messages
 pub mod message;
 /// The module to handle payloads
 pub mod payload;
 /// The rich presence module
 pub mod rich_presence;
+/// The voice settings module
+pub mod voice_settings;
 
 use quork::traits::list::ListVariants;
 
 /// Different Discord commands
-#[derive(Debug, PartialEq, Eq, Copy, Clone, Deserialize, Serialize)]
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize, Serialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum Command {
     /// Dispatch something to Discord
@@ -29,11 +37,31 @@ pub enum Command {
     SendActivityJoinInvite,
     /// Close the invite to join a game
     CloseActivityRequest,
+    /// Get the user's voice settings
+    GetVoiceSettings,
+    /// Set the user's voice settings
+    SetVoiceSettings,
+    /// Set the voice settings of another user, local to the current user
+    SetUserVoiceSettings,
+    /// A command that is not natively supported by this crate
+    #[serde(untagged)]
+    Other(String),
+}
+
+impl From<&str> for Command {
+    fn from(name: &str) -> Self {
+        Self::from(name.to_string())
+    }
+}
+
+impl From<String> for Command {
+    fn from(name: String) -> Self {
+        serde_json::from_value(JsonValue::String(name.clone())).unwrap_or(Self::Other(name))
+    }
 }
 
-// NOTE: ListVariants is required to bevy-discord-rpc
 /// Discord events
-#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, Copy, Clone, Hash, ListVariants)]
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, Clone, Hash)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum Event {
     /// [`Event::Ready`] event, fired when the client is ready, but not if an error occurs
@@ -44,18 +72,143 @@ pub enum Event {
     Disconnected,
     /// [`Event::Error`] event, overrides the `Ready` event
     Error,
+    /// [`Event::ConnectionAttempt`] event, fired when connecting fails, and the client is going to retry
+    ConnectionAttempt,
+    /// [`Event::ConnectionFailed`] event, fired when the client gives up connecting,
+    /// because its connection attempts are exhausted or a fatal error occurred
+    ConnectionFailed,
     /// [`Event::ActivityJoin`] event, fired when the client's game is joined by a player
     ActivityJoin,
     /// [`Event::ActivitySpectate`] event, fired when the client receives a spectate request
     ActivitySpectate,
     /// [`Event::ActivityJoinRequest`] event, fired when the client receives a join request
     ActivityJoinRequest,
+    /// [`Event::VoiceStateCreate`] event, fired when a user joins a subscribed voice channel
+    ///
+    /// Subscribe using [`SubscriptionArgs::Channel`]
+    VoiceStateCreate,
+    /// [`Event::VoiceStateUpdate`] event, fired when a user's voice state changes in a subscribed voice channel
+    ///
+    /// Subscribe using [`SubscriptionArgs::Channel`]
+    VoiceStateUpdate,
+    /// [`Event::VoiceStateDelete`] event, fired when a user parts a subscribed voice channel
+    ///
+    /// Subscribe using [`SubscriptionArgs::Channel`]
+    VoiceStateDelete,
+    /// [`Event::VoiceSettingsUpdate`] event, fired when the client's voice settings update
+    VoiceSettingsUpdate,
+    /// [`Event::VoiceConnectionStatus`] event, fired when the client's voice connection status changes
+    VoiceConnectionStatus,
+    /// [`Event::SpeakingStart`] event, fired when a user in a subscribed voice channel speaks
+    ///
+    /// Subscribe using [`SubscriptionArgs::Channel`]
+    SpeakingStart,
+    /// [`Event::SpeakingStop`] event, fired when a user in a subscribed voice channel stops speaking
+    ///
+    /// Subscribe using [`SubscriptionArgs::Channel`]
+    SpeakingStop,
+    /// [`Event::MessageCreate`] event, fired when a message is created in a subscribed text channel
+    ///
+    /// Subscribe using [`SubscriptionArgs::Channel`]
+    MessageCreate,
+    /// [`Event::MessageUpdate`] event, fired when a message is updated in a subscribed text channel
+    ///
+    /// Subscribe using [`SubscriptionArgs::Channel`]
+    MessageUpdate,
+    /// [`Event::MessageDelete`] event, fired when a message is deleted in a subscribed text channel
+    ///
+    /// Subscribe using [`SubscriptionArgs::Channel`]
+    MessageDelete,
+    /// [`Event::NotificationCreate`] event, fired when the client receives a notification
+    NotificationCreate,
+    /// [`Event::GuildStatus`] event, fired when a subscribed guild's state changes
+    ///
+    /// Subscribe using [`SubscriptionArgs::Guild`]
+    GuildStatus,
+    /// [`Event::GuildCreate`] event, fired when the user joins a guild
+    GuildCreate,
+    /// [`Event::ChannelCreate`] event, fired when a channel is created, or the user is added to a group DM
+    ChannelCreate,
+    /// An event that is not natively supported by this crate
+    ///
+    /// The event data is always [`EventData::Unknown`]
+    #[serde(untagged)]
+    Other(String),
+}
+
+/// Implements [`ListVariants`] for [`Event`], counting the listed variants
+macro_rules! list_events {
+    [ $($variant:ident),* $(,)? ] => {
+        impl ListVariants<{ [$(stringify!($variant)),*].len() }> for Event {
+            const VARIANTS: [Self; { [$(stringify!($variant)),*].len() }] = [$(Event::$variant),*];
+        }
+    };
+}
+
+// NOTE: ListVariants is required to bevy-discord-rpc
+// It is implemented manually, as [`Event::Other`] cannot be listed
+list_events![
+    Ready,
+    Connected,
+    Disconnected,
+    Error,
+    ConnectionAttempt,
+    ConnectionFailed,
+    ActivityJoin,
+    ActivitySpectate,
+    ActivityJoinRequest,
+    VoiceStateCreate,
+    VoiceStateUpdate,
+    VoiceStateDelete,
+    VoiceSettingsUpdate,
+    VoiceConnectionStatus,
+    SpeakingStart,
+    SpeakingStop,
+    MessageCreate,
+    MessageUpdate,
+    MessageDelete,
+    NotificationCreate,
+    GuildStatus,
+    GuildCreate,
+    ChannelCreate,
+];
+
+impl From<&str> for Event {
+    fn from(name: &str) -> Self {
+        Self::from(name.to_string())
+    }
+}
+
+impl From<String> for Event {
+    fn from(name: String) -> Self {
+        serde_json::from_value(JsonValue::String(name.clone())).unwrap_or(Self::Other(name))
+    }
 }
 
 impl Event {
+    #[must_use]
+    /// Whether the event is sent by Discord after subscribing to it, without any args
+    ///
+    /// The client subscribes to these events automatically when a handler is registered,
+    /// see [`Client::with_auto_subscribe`](crate::Client::with_auto_subscribe).
+    /// Events that need args, such as a channel id, must be subscribed to manually.
+    pub fn is_auto_subscribable(&self) -> bool {
+        matches!(
+            self,
+            Self::ActivityJoin
+                | Self::ActivitySpectate
+                | Self::ActivityJoinRequest
+                | Self::VoiceSettingsUpdate
+                | Self::VoiceConnectionStatus
+                | Self::NotificationCreate
+                | Self::GuildCreate
+                | Self::ChannelCreate
+        )
+    }
+
     #[must_use]
     /// Parse event data from a [`JsonValue`]
-    pub fn parse_data(self, data: JsonValue) -> EventData {
+    pub fn parse_data(&self, data: JsonValue) -> EventData {
         match self {
             Event::Ready => serde_json::from_value(data.clone())
                 .map(EventData::Ready)
@@ -65,6 +218,14 @@ impl Event {
                 .map(EventData::Error)
                 .unwrap_or(EventData::Unknown(data)),
 
+            Event::ConnectionAttempt => serde_json::from_value(data.clone())
+                .map(EventData::ConnectionAttempt)
+                .unwrap_or(EventData::Unknown(data)),
+
+            Event::ConnectionFailed => serde_json::from_value(data.clone())
+                .map(EventData::ConnectionFailed)
+                .unwrap_or(EventData::Unknown(data)),
+
             Event::ActivityJoin => serde_json::from_value(data.clone())
                 .map(EventData::ActivityJoin)
                 .unwrap_or(EventData::Unknown(data)),
@@ -77,45 +238,252 @@ impl Event {
                 .map(EventData::ActivityJoinRequest)
                 .unwrap_or(EventData::Unknown(data)),
 
+            Event::VoiceStateCreate => serde_json::from_value(data.clone())
+                .map(EventData::VoiceStateCreate)
+                .unwrap_or(EventData::Unknown(data)),
+
+            Event::VoiceStateUpdate => serde_json::from_value(data.clone())
+                .map(EventData::VoiceStateUpdate)
+                .unwrap_or(EventData::Unknown(data)),
+
+            Event::VoiceStateDelete => serde_json::from_value(data.clone())
+                .map(EventData::VoiceStateDelete)
+                .unwrap_or(EventData::Unknown(data)),
+
+            Event::VoiceSettingsUpdate => serde_json::from_value(data.clone())
+                .map(EventData::VoiceSettingsUpdate)
+                .unwrap_or(EventData::Unknown(data)),
+
+            Event::VoiceConnectionStatus => serde_json::from_value(data.clone())
+                .map(EventData::VoiceConnectionStatus)
+                .unwrap_or(EventData::Unknown(data)),
+
+            Event::SpeakingStart => serde_json::from_value(data.clone())
+                .map(EventData::SpeakingStart)
+                .unwrap_or(EventData::Unknown(data)),
+
+            Event::SpeakingStop => serde_json::from_value(data.clone())
+                .map(EventData::SpeakingStop)
+                .unwrap_or(EventData::Unknown(data)),
+
+            Event::MessageCreate => serde_json::from_value(data.clone())
+                .map(EventData::MessageCreate)
+                .unwrap_or(EventData::Unknown(data)),
+
+            Event::MessageUpdate => serde_json::from_value(data.clone())
+                .map(EventData::MessageUpdate)
+                .unwrap_or(EventData::Unknown(data)),
+
+            Event::MessageDelete => serde_json::from_value(data.clone())
+                .map(EventData::MessageDelete)
+                .unwrap_or(EventData::Unknown(data)),
+
+            Event::NotificationCreate => serde_json::from_value(data.clone())
+                .map(EventData::NotificationCreate)
+                .unwrap_or(EventData::Unknown(data)),
+
+            Event::GuildStatus => serde_json::from_value(data.clone())
+                .map(EventData::GuildStatus)
+                .unwrap_or(EventData::Unknown(data)),
+
+            Event::GuildCreate => serde_json::from_value(data.clone())
+                .map(EventData::GuildCreate)
+                .unwrap_or(EventData::Unknown(data)),
+
+            Event::ChannelCreate => serde_json::from_value(data.clone())
+                .map(EventData::ChannelCreate)
+                .unwrap_or(EventData::Unknown(data)),
+
+            Event::Other(_) => EventData::Unknown(data),
+
             Event::Connected | Event::Disconnected => EventData::None,
         }
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, Clone)]
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
 /// Internal data for the [`Event`] enum
 pub enum EventData {
     /// [`Event::Ready`] event data
     Ready(ReadyEvent),
     /// [`Event::Error`] event data
     Error(ErrorEvent),
+    /// [`Event::ConnectionAttempt`] event data
+    ConnectionAttempt(ConnectionAttemptEvent),
+    /// [`Event::ConnectionFailed`] event data
+    ConnectionFailed(ConnectionFailedEvent),
     /// [`Event::ActivityJoin`] event data
     ActivityJoin(ActivityJoinEvent),
     /// [`Event::ActivitySpectate`] event data
     ActivitySpectate(ActivitySpectateEvent),
     /// [`Event::ActivityJoinRequest`] event data
     ActivityJoinRequest(ActivityJoinRequestEvent),
+    /// [`Event::VoiceStateCreate`] event data
+    VoiceStateCreate(VoiceStateEvent),
+    /// [`Event::VoiceStateUpdate`] event data
+    VoiceStateUpdate(VoiceStateEvent),
+    /// [`Event::VoiceStateDelete`] event data
+    VoiceStateDelete(VoiceStateEvent),
+    /// [`Event::VoiceSettingsUpdate`] event data
+    VoiceSettingsUpdate(VoiceSettings),
+    /// [`Event::VoiceConnectionStatus`] event data
+    VoiceConnectionStatus(VoiceConnectionStatusEvent),
+    /// [`Event::SpeakingStart`] event data
+    SpeakingStart(SpeakingEvent),
+    /// [`Event::SpeakingStop`] event data
+    SpeakingStop(SpeakingEvent),
+    /// [`Event::MessageCreate`] event data
+    MessageCreate(MessageEvent),
+    /// [`Event::MessageUpdate`] event data
+    MessageUpdate(MessageEvent),
+    /// [`Event::MessageDelete`] event data
+    ///
+    /// Only the message `id` is set
+    MessageDelete(MessageEvent),
+    /// [`Event::NotificationCreate`] event data
+    NotificationCreate(NotificationCreateEvent),
+    /// [`Event::GuildStatus`] event data
+    GuildStatus(GuildStatusEvent),
+    /// [`Event::GuildCreate`] event data
+    GuildCreate(GuildCreateEvent),
+    /// [`Event::ChannelCreate`] event data
+    ChannelCreate(ChannelCreateEvent),
     /// Unknown event data
     Unknown(JsonValue),
     /// Event had no data
     None,
 }
 
+pub use chat::{MessageEvent, NotificationCreateEvent};
 pub use commands::*;
+pub use event_types::RpcEvent;
 pub use events::*;
+pub use guild::*;
 pub use message::{Message, OpCode};
 
 pub use rich_presence::*;
 use serde_json::Value as JsonValue;
+pub use voice_settings::*;
 
 /// Prelude for all Discord RPC types
 pub mod prelude {
-    pub use super::commands::{Subscription, SubscriptionArgs};
-    pub use super::events::{ErrorEvent, ReadyEvent};
+    pub use super::chat::{
+        Embed, Message as ChatMessage, MessageAttachment, MessageEvent, NotificationCreateEvent,
+    };
+    pub use super::commands::{
+        AuthorizeArgs, AuthorizeResponse, ChannelSubscriptionArgs, EmptyArgs,
+        GuildSubscriptionArgs, RpcCommand, SubscribeArgs, Subscription, SubscriptionArgs,
+    };
+    pub use super::event_types::RpcEvent;
+    pub use super::events::{
+        ConnectionAttemptEvent, ConnectionFailedEvent, ErrorEvent, ReadyEvent, SpeakingEvent,
+        VoiceConnectionState, VoiceConnectionStatusEvent, VoiceStateEvent,
+    };
+    pub use super::guild::{ChannelCreateEvent, GuildCreateEvent, GuildStatusEvent};
     pub use super::rich_presence::{
         ActivityJoinEvent, ActivityJoinRequestEvent, ActivitySpectateEvent,
         CloseActivityRequestArgs, SendActivityJoinInviteArgs, SetActivityArgs,
     };
+    pub use super::voice_settings::{
+        Pan, ShortcutKeyCombo, ShortcutKeyType, UserVoiceSettings, VoiceModeType, VoiceSettings,
+    };
     pub use super::Command;
     pub use super::Event;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn can_parse_voice_event_data() {
+        let data = Event::SpeakingStart.parse_data(json!({ "user_id": "1234" }));
+        assert_eq!(
+            data,
+            EventData::SpeakingStart(SpeakingEvent::new().user_id("1234"))
+        );
+
+        let data = Event::VoiceStateUpdate.parse_data(json!({
+            "voice_state": { "mute": false, "self_mute": true },
+            "user": { "id": "1234", "username": "ferris", "bot": false },
+            "volume": 100,
+            "pan": { "left": 1.0, "right": 0.5 }
+        }));
+        let EventData::VoiceStateUpdate(state) = data else {
+            panic!("Failed to parse voice state event");
+        };
+        assert_eq!(state.voice_state.and_then(|s| s.self_mute), Some(true));
+        assert_eq!(state.pan.and_then(|p| p.right), Some(0.5));
+    }
+
+    #[test]
+    fn lists_every_known_event() {
+        let names: std::collections::HashSet<String> = Event::VARIANTS
+            .iter()
+            .map(|event| {
+                serde_json::to_value(event)
+                    .unwrap()
+                    .as_str()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(names.len(), Event::VARIANTS.len());
+
+        for name in names {
+            assert!(!matches!(Event::from(name), Event::Other(_)));
+        }
+    }
+
+    #[test]
+    fn can_parse_unknown_events_and_commands() {
+        let event: Event = serde_json::from_str(r#""READY""#).unwrap();
+        assert_eq!(event, Event::Ready);
+
+        let event: Event = serde_json::from_str(r#""LOBBY_UPDATE""#).unwrap();
+        assert_eq!(event, Event::Other(String::from("LOBBY_UPDATE")));
+        assert_eq!(Event::from("LOBBY_UPDATE"), event);
+        assert_eq!(serde_json::to_string(&event).unwrap(), r#""LOBBY_UPDATE""#);
+
+        assert_eq!(Command::from("SET_ACTIVITY"), Command::SetActivity);
+        assert_eq!(
+            serde_json::to_string(&Command::from("GET_GUILDS")).unwrap(),
+            r#""GET_GUILDS""#
+        );
+
+        let payload: payload::Payload<JsonValue> =
+            serde_json::from_str(r#"{"cmd":"DISPATCH","evt":"LOBBY_UPDATE","data":{"id":"1"}}"#)
+                .unwrap();
+        assert_eq!(
+            payload.evt,
+            Some(Event::Other(String::from("LOBBY_UPDATE")))
+        );
+        assert_eq!(
+            Event::from("LOBBY_UPDATE").parse_data(json!({ "id": "1" })),
+            EventData::Unknown(json!({ "id": "1" }))
+        );
+    }
+
+    #[test]
+    fn lists_known_event_variants() {
+        assert!(!Event::VARIANTS.contains(&Event::Other(String::new())));
+        assert!(Event::VARIANTS.contains(&Event::ChannelCreate));
+    }
+
+    #[test]
+    fn can_parse_guild_event_data() {
+        let data = Event::GuildStatus.parse_data(json!({
+            "guild": { "id": "1234", "name": "Rustaceans", "icon_url": null },
+            "online": 42
+        }));
+        assert_eq!(
+            data,
+            EventData::GuildStatus(
+                GuildStatusEvent::new()
+                    .guild(|g| g.id("1234").name("Rustaceans"))
+                    .online(42)
+            )
+        );
+    }
+}
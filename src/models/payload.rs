@@ -1,23 +1,23 @@
-use super::{Command, Event, Message};
-use crate::utils;
+use super::{events::ErrorEvent, Command, Event, Message};
+use crate::{error::RpcErrorCode, utils, DiscordError};
 use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value as JsonValue;
 
 /// The Discord client payload
+///
+/// The `args` sent with a command, and the `data` Discord responds with, are typed independently.
 #[derive(Debug, PartialEq, Eq, Deserialize, Serialize)]
-pub struct Payload<T>
-where
-    T: Serialize,
-{
+pub struct Payload<A = JsonValue, D = JsonValue> {
     /// The payload command
     pub cmd: Command,
 
     /// The payload args
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub args: Option<T>,
+    pub args: Option<A>,
 
     /// The payload data
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub data: Option<T>,
+    pub data: Option<D>,
 
     /// The payload event
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -28,12 +28,9 @@ where
     pub nonce: Option<String>,
 }
 
-impl<T> Payload<T>
-where
-    T: Serialize,
-{
+impl<A, D> Payload<A, D> {
     /// Create a `Payload`, by generating a nonce
-    pub fn with_nonce(cmd: Command, args: Option<T>, data: Option<T>, evt: Option<Event>) -> Self {
+    pub fn with_nonce(cmd: Command, args: Option<A>, data: Option<D>, evt: Option<Event>) -> Self {
         Self {
             cmd,
             args,
@@ -44,11 +41,119 @@ where
     }
 }
 
-impl<T> From<Message> for Payload<T>
+impl Payload {
+    /// Convert a raw response into a typed response, or the error Discord responded with
+    pub(crate) fn into_response<A, D>(self) -> crate::Result<Payload<A, D>>
+    where
+        A: DeserializeOwned,
+        D: DeserializeOwned,
+    {
+        if self.evt == Some(Event::Error) {
+            let error: ErrorEvent = self
+                .data
+                .map(serde_json::from_value)
+                .transpose()?
+                .unwrap_or_default();
+
+            return Err(DiscordError::Rpc {
+                code: error
+                    .code
+                    .map_or(RpcErrorCode::UnknownError, RpcErrorCode::from),
+                message: error.message.unwrap_or_default(),
+                command: self.cmd,
+            });
+        }
+
+        Ok(Payload {
+            cmd: self.cmd,
+            args: self.args.map(serde_json::from_value).transpose()?,
+            data: self.data.map(serde_json::from_value).transpose()?,
+            evt: self.evt,
+            nonce: self.nonce,
+        })
+    }
+}
+
+impl<A, D> Payload<A, D>
+where
+    A: Serialize,
+    D: Serialize,
+{
+    /// Convert the args and data to JSON values
+    pub(crate) fn into_value(self) -> Result<Payload, DiscordError> {
+        Ok(Payload {
+            cmd: self.cmd,
+            args: self.args.map(serde_json::to_value).transpose()?,
+            data: self.data.map(serde_json::to_value).transpose()?,
+            evt: self.evt,
+            nonce: self.nonce,
+        })
+    }
+}
+
+impl<A, D> TryFrom<Message> for Payload<A, D>
 where
-    T: Serialize + DeserializeOwned,
+    A: DeserializeOwned,
+    D: DeserializeOwned,
 {
-    fn from(message: Message) -> Self {
-        serde_json::from_str(&message.payload).unwrap()
+    type Error = DiscordError;
+
+    fn try_from(message: Message) -> Result<Self, Self::Error> {
+        Ok(serde_json::from_str(&message.payload)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{rich_presence::Activity, OpCode, SetActivityArgs};
+
+    #[test]
+    fn can_parse_args_and_data_separately() {
+        let message = Message {
+            opcode: OpCode::Frame,
+            payload: format!(
+                r#"{{"cmd":"SET_ACTIVITY","args":{{"pid":{}}},"data":{{"state":"rusting"}}}}"#,
+                utils::pid()
+            ),
+        };
+
+        let payload = Payload::<SetActivityArgs, Activity>::try_from(message).unwrap();
+        assert_eq!(payload.args, Some(SetActivityArgs::default()));
+        assert_eq!(payload.data, Some(Activity::new().state("rusting")));
+    }
+
+    #[test]
+    fn converts_error_responses() {
+        let payload: Payload = serde_json::from_str(
+            r#"{"cmd":"SET_ACTIVITY","evt":"ERROR","data":{"code":4000,"message":"child \"activity\" fails"}}"#,
+        )
+        .unwrap();
+
+        let Err(DiscordError::Rpc {
+            code,
+            message,
+            command,
+        }) = payload.into_response::<SetActivityArgs, Activity>()
+        else {
+            panic!("Expected an RPC error");
+        };
+
+        assert_eq!(code, RpcErrorCode::InvalidPayload);
+        assert_eq!(message, r#"child "activity" fails"#);
+        assert_eq!(command, Command::SetActivity);
+    }
+
+    #[test]
+    fn fails_to_parse_invalid_message() {
+        let message = Message {
+            opcode: OpCode::Frame,
+            payload: String::from("not json"),
+        };
+
+        assert!(matches!(
+            Payload::<JsonValue>::try_from(message),
+            Err(DiscordError::JsonError(_))
+        ));
     }
 }
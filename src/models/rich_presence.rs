@@ -102,7 +102,7 @@ builder! {Activity
     assets: ActivityAssets func,
     party: ActivityParty func,
     secrets: ActivitySecrets func,
-    buttons: ActivityButton as array,
+    buttons: ActivityButton as array deserialize_with = "serialize_activity_button",
 }
 
 builder! {ActivityTimestamps
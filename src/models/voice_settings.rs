@@ -0,0 +1,285 @@
+use crate::{DiscordError, Result};
+
+/// The voice mode used by the user
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum VoiceModeType {
+    /// Transmit voice when the user is speaking (VAD)
+    VoiceActivity,
+    /// Transmit voice only while the shortcut is held (PTT)
+    PushToTalk,
+}
+
+/// The type of key in a [`ShortcutKeyCombo`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum ShortcutKeyType {
+    /// A regular keyboard key
+    KeyboardKey = 0,
+    /// A mouse button
+    MouseButton = 1,
+    /// A keyboard modifier key, such as shift or control
+    KeyboardModifierKey = 2,
+    /// A gamepad button
+    GamepadButton = 3,
+}
+
+impl TryFrom<u8> for ShortcutKeyType {
+    type Error = DiscordError;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(Self::KeyboardKey),
+            1 => Ok(Self::MouseButton),
+            2 => Ok(Self::KeyboardModifierKey),
+            3 => Ok(Self::GamepadButton),
+            _ => Err(DiscordError::Conversion),
+        }
+    }
+}
+
+impl From<ShortcutKeyType> for u8 {
+    fn from(value: ShortcutKeyType) -> Self {
+        value as u8
+    }
+}
+
+builder! {partial_eq VoiceSettings
+    input: VoiceSettingsInput func,
+    output: VoiceSettingsOutput func,
+    mode: VoiceSettingsMode func,
+    automatic_gain_control: bool,
+    echo_cancellation: bool,
+    noise_suppression: bool,
+    qos: bool,
+    silence_warning: bool,
+    deaf: bool,
+    mute: bool,
+}
+
+builder! {partial_eq VoiceSettingsInput
+    device_id: String,
+    // Input volume (0-100)
+    volume: f32,
+    // Read-only list of the available input devices
+    available_devices: AvailableDevice as array,
+}
+
+builder! {partial_eq VoiceSettingsOutput
+    device_id: String,
+    // Output volume (0-200)
+    volume: f32,
+    // Read-only list of the available output devices
+    available_devices: AvailableDevice as array,
+}
+
+builder! {partial_eq VoiceSettingsMode
+    _type: VoiceModeType alias = "type",
+    auto_threshold: bool,
+    // Voice activity threshold in dB (-100-0)
+    threshold: f32,
+    shortcut: ShortcutKeyCombo as array,
+    // Push to talk release delay in milliseconds (0-2000)
+    delay: f32,
+}
+
+builder! {AvailableDevice
+    id: String,
+    name: String,
+}
+
+builder! {ShortcutKeyCombo
+    _type: ShortcutKeyType alias = "type",
+    code: u32,
+    name: String,
+}
+
+builder! {partial_eq UserVoiceSettings
+    user_id: String,
+    pan: Pan func,
+    // Volume of the user (0-200)
+    volume: u32,
+    mute: bool,
+}
+
+builder! {partial_eq Pan
+    // Left pan of the user (0.0-1.0)
+    left: f32,
+    // Right pan of the user (0.0-1.0)
+    right: f32,
+}
+
+fn validate_range<T>(field: &'static str, value: Option<T>, min: T, max: T) -> Result<()>
+where
+    T: Copy + PartialOrd + Into<f64>,
+{
+    match value {
+        // Also rejects NaN, which would be sent as `null`
+        Some(value) if !(min..=max).contains(&value) => Err(DiscordError::OutOfRange {
+            field,
+            value: value.into(),
+            min: min.into(),
+            max: max.into(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+impl VoiceSettings {
+    /// Check that all set values are within the ranges accepted by Discord
+    ///
+    /// # Errors
+    /// - A value is out of range
+    pub fn validate(&self) -> Result<()> {
+        if let Some(ref input) = self.input {
+            validate_range("input.volume", input.volume, 0.0, 100.0)?;
+        }
+
+        if let Some(ref output) = self.output {
+            validate_range("output.volume", output.volume, 0.0, 200.0)?;
+        }
+
+        if let Some(ref mode) = self.mode {
+            validate_range("mode.threshold", mode.threshold, -100.0, 0.0)?;
+            validate_range("mode.delay", mode.delay, 0.0, 2000.0)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl UserVoiceSettings {
+    /// Check that all set values are within the ranges accepted by Discord
+    ///
+    /// # Errors
+    /// - A value is out of range
+    pub fn validate(&self) -> Result<()> {
+        validate_range("volume", self.volume, 0, 200)?;
+
+        if let Some(ref pan) = self.pan {
+            validate_range("pan.left", pan.left, 0.0, 1.0)?;
+            validate_range("pan.right", pan.right, 0.0, 1.0)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_deserialize_full_voice_settings() {
+        let json = include_str!("../../tests/fixtures/voice_settings_full.json");
+        let parsed = serde_json::from_str::<VoiceSettings>(json).unwrap();
+
+        let settings = VoiceSettings::new()
+            .input(|i| {
+                i.device_id("default")
+                    .volume(100.0)
+                    .append_available_devices(|d| d.id("default").name("Default"))
+            })
+            .output(|o| {
+                o.device_id("default")
+                    .volume(150.5)
+                    .append_available_devices(|d| d.id("default").name("Default"))
+            })
+            .mode(|m| {
+                m._type(VoiceModeType::PushToTalk)
+                    .auto_threshold(true)
+                    .threshold(-60.0)
+                    .append_shortcut(|s| s._type(ShortcutKeyType::KeyboardKey).code(12).name("i"))
+                    .delay(20.0)
+            })
+            .automatic_gain_control(true)
+            .echo_cancellation(true)
+            .noise_suppression(false)
+            .qos(false)
+            .silence_warning(true)
+            .deaf(false)
+            .mute(true);
+
+        assert_eq!(parsed, settings);
+    }
+
+    #[test]
+    fn can_serialize_mute_and_deafen() {
+        let settings = VoiceSettings::new().mute(true).deaf(false);
+        let json = serde_json::to_string(&settings).expect("Failed to serialize into String");
+
+        assert_eq![json, r#"{"deaf":false,"mute":true}"#];
+    }
+
+    #[test]
+    fn validates_voice_settings_ranges() {
+        assert!(VoiceSettings::new()
+            .input(|i| i.volume(100.0))
+            .output(|o| o.volume(200.0))
+            .validate()
+            .is_ok());
+
+        assert!(matches!(
+            VoiceSettings::new().input(|i| i.volume(150.0)).validate(),
+            Err(DiscordError::OutOfRange {
+                field: "input.volume",
+                ..
+            })
+        ));
+
+        assert!(matches!(
+            VoiceSettings::new()
+                .output(|o| o.volume(f32::NAN))
+                .validate(),
+            Err(DiscordError::OutOfRange {
+                field: "output.volume",
+                ..
+            })
+        ));
+
+        assert!(matches!(
+            VoiceSettings::new()
+                .mode(|m| m.threshold(f32::NAN))
+                .validate(),
+            Err(DiscordError::OutOfRange {
+                field: "mode.threshold",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn validates_user_voice_settings_ranges() {
+        let settings = UserVoiceSettings::new()
+            .user_id("1234")
+            .volume(200)
+            .pan(|p| p.left(0.25).right(1.0));
+        assert!(settings.validate().is_ok());
+
+        assert!(matches!(
+            UserVoiceSettings::new().volume(201).validate(),
+            Err(DiscordError::OutOfRange {
+                field: "volume",
+                ..
+            })
+        ));
+
+        assert!(matches!(
+            UserVoiceSettings::new().pan(|p| p.left(-0.5)).validate(),
+            Err(DiscordError::OutOfRange {
+                field: "pan.left",
+                ..
+            })
+        ));
+
+        assert!(matches!(
+            UserVoiceSettings::new()
+                .pan(|p| p.right(f32::NAN))
+                .validate(),
+            Err(DiscordError::OutOfRange {
+                field: "pan.right",
+                ..
+            })
+        ));
+    }
+}
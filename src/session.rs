@@ -0,0 +1,430 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::File,
+    io::{BufRead, BufReader, ErrorKind, Write},
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use parking_lot::{Condvar, Mutex};
+use serde_json::{json, Value as JsonValue};
+
+use crate::{
+    models::{Message, OpCode},
+    Result,
+};
+
+/// What a [`SessionEntry`] records
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    /// A frame sent to Discord
+    Sent,
+    /// A frame received from Discord
+    Received,
+    /// The connection was closed by Discord, or failed
+    Closed,
+}
+
+/// A single line of a recorded session, see [`Client::with_session_recording`](crate::Client::with_session_recording)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionEntry {
+    /// Milliseconds since the recording started
+    pub elapsed_ms: u64,
+    /// What the entry records
+    pub kind: EntryKind,
+    /// The opcode of the frame, unless the entry is [`EntryKind::Closed`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opcode: Option<OpCode>,
+    /// The payload of the frame, unless the entry is [`EntryKind::Closed`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<JsonValue>,
+}
+
+/// Writes every frame exchanged with Discord to a JSONL file, one [`SessionEntry`] per line
+pub(crate) struct SessionRecorder {
+    file: Mutex<File>,
+    started: Instant,
+}
+
+impl SessionRecorder {
+    pub fn create(path: &Path) -> Result<Self> {
+        Ok(Self {
+            file: Mutex::new(File::create(path)?),
+            started: Instant::now(),
+        })
+    }
+
+    pub fn sent(&self, message: &Message) {
+        self.record(EntryKind::Sent, Some(message));
+    }
+
+    pub fn received(&self, message: &Message) {
+        self.record(EntryKind::Received, Some(message));
+    }
+
+    pub fn closed(&self) {
+        self.record(EntryKind::Closed, None);
+    }
+
+    fn record(&self, kind: EntryKind, message: Option<&Message>) {
+        let entry = SessionEntry {
+            elapsed_ms: u64::try_from(self.started.elapsed().as_millis()).unwrap_or(u64::MAX),
+            kind,
+            opcode: message.map(|message| message.opcode),
+            // Kept as a string if it is not valid JSON, so that nothing is lost
+            payload: message.map(|message| {
+                serde_json::from_str(&message.payload)
+                    .unwrap_or_else(|_| JsonValue::String(message.payload.clone()))
+            }),
+        };
+
+        // Written line by line, so that the recording survives a crash
+        let result = serde_json::to_string(&entry)
+            .map_err(std::io::Error::from)
+            .and_then(|line| writeln!(self.file.lock(), "{line}"));
+
+        if let Err(err) = result {
+            error!("Failed to record {kind:?} frame: {err}");
+        }
+    }
+}
+
+#[derive(Default)]
+struct ReplayState {
+    entries: VecDeque<SessionEntry>,
+    // Frames sent by the client, that have not been matched to a recorded frame yet
+    sent: VecDeque<Message>,
+    // Responses to frames that are not part of the recording
+    rejected: VecDeque<Message>,
+    // Recorded nonces, mapped to the nonces of the frames sent by the client
+    nonces: HashMap<String, String>,
+}
+
+/// A recorded session, replayed instead of connecting to Discord, see [`Client::with_replay`](crate::Client::with_replay)
+///
+/// Recorded frames are replayed in order, as fast as the client reads them, so the replay does not depend on the recorded timing:
+///
+/// - A received frame is handed to the client as if Discord sent it.
+/// - A sent frame waits for the client to send a frame with the same opcode and command,
+///   whose nonce then replaces the recorded one in the responses.
+///   Other frames are answered with an error, or rejected if they are handshakes.
+/// - A closed entry closes the connection, after which the client reconnects to the rest of the recording.
+///
+/// Once every entry has been replayed, the connection stays open, but commands fail, and reconnecting is rejected.
+///
+/// Clones share the same replay.
+#[derive(Clone, Default)]
+pub struct Replay {
+    state: Arc<(Mutex<ReplayState>, Condvar)>,
+}
+
+impl Replay {
+    /// Replay the given entries
+    pub fn new<I>(entries: I) -> Self
+    where
+        I: IntoIterator<Item = SessionEntry>,
+    {
+        let state = ReplayState {
+            entries: entries.into_iter().collect(),
+            ..ReplayState::default()
+        };
+
+        Self {
+            state: Arc::new((Mutex::new(state), Condvar::new())),
+        }
+    }
+
+    /// Replay a session recorded by [`Client::with_session_recording`](crate::Client::with_session_recording)
+    ///
+    /// # Errors
+    /// - The file could not be read
+    /// - A line is not a valid [`SessionEntry`]
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut entries = Vec::new();
+
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                entries.push(serde_json::from_str(&line)?);
+            }
+        }
+
+        Ok(Self::new(entries))
+    }
+
+    #[must_use]
+    /// The number of entries that have not been replayed yet
+    pub fn remaining(&self) -> usize {
+        self.state.0.lock().entries.len()
+    }
+
+    #[must_use]
+    /// Whether every entry has been replayed
+    pub fn is_finished(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Block until every entry has been replayed, or the timeout elapses
+    ///
+    /// Returns whether the replay finished.
+    #[must_use]
+    pub fn wait_until_finished(&self, timeout: Duration) -> bool {
+        let (state, finished) = &*self.state;
+        let mut state = state.lock();
+
+        let deadline = Instant::now() + timeout;
+        while !state.entries.is_empty() {
+            if finished.wait_until(&mut state, deadline).timed_out() {
+                return state.entries.is_empty();
+            }
+        }
+
+        true
+    }
+
+    pub(crate) fn send(&self, message: &Message) {
+        self.state.0.lock().sent.push_back(message.clone());
+    }
+
+    /// The next frame for the client, or an error that would block if it has to wait for the client
+    pub(crate) fn recv(&self) -> Result<Message> {
+        let (state, finished) = &*self.state;
+        let mut state = state.lock();
+
+        let result = state.next();
+
+        if state.entries.is_empty() {
+            finished.notify_all();
+        }
+
+        result
+    }
+}
+
+impl ReplayState {
+    fn next(&mut self) -> Result<Message> {
+        loop {
+            if let Some(response) = self.rejected.pop_front() {
+                return Ok(response);
+            }
+
+            let Some(kind) = self.entries.front().map(|entry| entry.kind) else {
+                // Nothing is left to match the client's frames against
+                if let Some(sent) = self.sent.pop_front() {
+                    self.reject(&sent);
+                    continue;
+                }
+
+                return Err(std::io::Error::from(ErrorKind::WouldBlock).into());
+            };
+
+            match kind {
+                EntryKind::Sent => {
+                    let Some(sent) = self.sent.pop_front() else {
+                        return Err(std::io::Error::from(ErrorKind::WouldBlock).into());
+                    };
+
+                    let entry = &self.entries[0];
+                    let recorded = entry.payload.as_ref().unwrap_or(&JsonValue::Null);
+                    let actual: JsonValue =
+                        serde_json::from_str(&sent.payload).unwrap_or(JsonValue::Null);
+
+                    if entry.opcode != Some(sent.opcode) || recorded["cmd"] != actual["cmd"] {
+                        self.reject(&sent);
+                        continue;
+                    }
+
+                    if let (Some(recorded), Some(actual)) =
+                        (recorded["nonce"].as_str(), actual["nonce"].as_str())
+                    {
+                        let (recorded, actual) = (recorded.to_owned(), actual.to_owned());
+                        self.nonces.insert(recorded, actual);
+                    }
+
+                    self.entries.pop_front();
+                }
+                EntryKind::Received => {
+                    let Some(SessionEntry {
+                        opcode: Some(opcode),
+                        payload: Some(mut payload),
+                        ..
+                    }) = self.entries.pop_front()
+                    else {
+                        warn!("Skipping a received entry without a frame");
+                        continue;
+                    };
+
+                    let actual = payload["nonce"]
+                        .as_str()
+                        .and_then(|nonce| self.nonces.get(nonce))
+                        .cloned();
+                    if let Some(actual) = actual {
+                        payload["nonce"] = JsonValue::String(actual);
+                    }
+
+                    return Ok(Message {
+                        opcode,
+                        payload: payload.to_string(),
+                    });
+                }
+                EntryKind::Closed => {
+                    self.entries.pop_front();
+                    return Err(crate::DiscordError::ConnectionClosed);
+                }
+            }
+        }
+    }
+
+    /// Answer a frame that is not part of the recording
+    fn reject(&mut self, sent: &Message) {
+        let payload: JsonValue = serde_json::from_str(&sent.payload).unwrap_or(JsonValue::Null);
+        warn!(
+            "Replay received an unexpected {:?} frame: {payload}",
+            sent.opcode
+        );
+
+        let response = match sent.opcode {
+            OpCode::Frame => json!({
+                "cmd": payload["cmd"],
+                "evt": "ERROR",
+                "nonce": payload["nonce"],
+                "data": {
+                    "code": 1000,
+                    "message": "The command is not part of the replayed session",
+                },
+            }),
+            OpCode::Handshake => json!({
+                "code": 4000,
+                "message": "The handshake is not part of the replayed session",
+            }),
+            // Pings, pongs and closes are not answered
+            _ => return,
+        };
+
+        let opcode = if sent.opcode == OpCode::Handshake {
+            OpCode::Close
+        } else {
+            OpCode::Frame
+        };
+
+        self.rejected.push_back(Message {
+            opcode,
+            payload: response.to_string(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DiscordError;
+
+    fn entry(kind: EntryKind, payload: JsonValue) -> SessionEntry {
+        SessionEntry {
+            elapsed_ms: 0,
+            kind,
+            opcode: Some(OpCode::Frame),
+            payload: Some(payload),
+        }
+    }
+
+    fn frame(payload: &JsonValue) -> Message {
+        Message::new(OpCode::Frame, payload).unwrap()
+    }
+
+    fn payload(message: &Message) -> JsonValue {
+        serde_json::from_str(&message.payload).unwrap()
+    }
+
+    fn would_block(result: &Result<Message>) -> bool {
+        matches!(result, Err(err) if err.io_would_block())
+    }
+
+    #[test]
+    fn waits_for_sent_frames_and_maps_nonces() {
+        let replay = Replay::new([
+            entry(
+                EntryKind::Received,
+                json!({ "cmd": "DISPATCH", "evt": "ACTIVITY_JOIN" }),
+            ),
+            entry(
+                EntryKind::Sent,
+                json!({ "cmd": "SET_ACTIVITY", "nonce": "recorded" }),
+            ),
+            entry(
+                EntryKind::Received,
+                json!({ "cmd": "SET_ACTIVITY", "nonce": "recorded" }),
+            ),
+        ]);
+
+        assert_eq!(payload(&replay.recv().unwrap())["evt"], "ACTIVITY_JOIN");
+        assert!(would_block(&replay.recv()));
+
+        replay.send(&frame(&json!({ "cmd": "SET_ACTIVITY", "nonce": "actual" })));
+        let response = payload(&replay.recv().unwrap());
+        assert_eq!(response["cmd"], "SET_ACTIVITY");
+        assert_eq!(response["nonce"], "actual");
+
+        assert!(replay.is_finished());
+        assert!(replay.wait_until_finished(Duration::ZERO));
+        assert!(would_block(&replay.recv()));
+    }
+
+    #[test]
+    fn rejects_unexpected_frames() {
+        let replay = Replay::new([
+            entry(EntryKind::Sent, json!({ "cmd": "SET_ACTIVITY" })),
+            SessionEntry {
+                elapsed_ms: 0,
+                kind: EntryKind::Closed,
+                opcode: None,
+                payload: None,
+            },
+        ]);
+
+        replay.send(&frame(
+            &json!({ "cmd": "GET_VOICE_SETTINGS", "nonce": "1" }),
+        ));
+        let response = payload(&replay.recv().unwrap());
+        assert_eq!(response["evt"], "ERROR");
+        assert_eq!(response["nonce"], "1");
+        assert_eq!(replay.remaining(), 2);
+
+        replay.send(&frame(&json!({ "cmd": "SET_ACTIVITY" })));
+        assert!(matches!(replay.recv(), Err(DiscordError::ConnectionClosed)));
+
+        // Reconnecting after the end of the recording is rejected
+        replay.send(&Message::new(OpCode::Handshake, json!({ "v": 1 })).unwrap());
+        assert_eq!(replay.recv().unwrap().opcode, OpCode::Close);
+    }
+
+    #[test]
+    fn records_and_reads_back_sessions() {
+        let path = std::env::temp_dir().join(format!(
+            "discord-presence-session-{}.jsonl",
+            std::process::id()
+        ));
+
+        let recorder = SessionRecorder::create(&path).unwrap();
+        recorder.sent(&frame(&json!({ "cmd": "SET_ACTIVITY", "nonce": "1" })));
+        recorder.received(&Message {
+            opcode: OpCode::Frame,
+            payload: String::from("not json"),
+        });
+        recorder.closed();
+        drop(recorder);
+
+        let replay = Replay::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let entries = &replay.state.0.lock().entries;
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].kind, EntryKind::Sent);
+        assert_eq!(entries[0].opcode, Some(OpCode::Frame));
+        assert_eq!(entries[1].payload, Some(json!("not json")));
+        assert_eq!(entries[2].kind, EntryKind::Closed);
+        assert_eq!(entries[2].payload, None);
+    }
+}
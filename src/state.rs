@@ -0,0 +1,160 @@
+use std::{sync::Arc, time::Duration};
+
+use parking_lot::{Condvar, Mutex};
+
+/// The state of the client's connection to Discord
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[allow(clippy::module_name_repetitions)]
+pub enum ConnectionState {
+    /// The client has not been started
+    #[default]
+    Idle,
+    /// Discord is not running, and the client is waiting for its IPC socket to appear
+    Discovering,
+    /// The client is connecting to Discord for the first time
+    Connecting,
+    /// The client is connected, and is performing the handshake
+    Handshaking,
+    /// The handshake completed, and the client is ready to send commands
+    Ready,
+    /// The client lost its connection, or failed to connect, and is trying again
+    Reconnecting,
+    /// The client was stopped
+    Closed,
+    /// The client gave up connecting to Discord
+    Failed,
+}
+
+impl ConnectionState {
+    #[must_use]
+    /// Whether the client will not change state anymore, without being restarted
+    pub fn is_terminal(self) -> bool {
+        matches!(self, Self::Closed | Self::Failed)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Versioned {
+    state: ConnectionState,
+    version: u64,
+}
+
+/// Shared connection state, updated by the connection manager
+#[derive(Debug, Default)]
+pub(crate) struct SharedState {
+    current: Mutex<Versioned>,
+    changed: Condvar,
+}
+
+impl SharedState {
+    pub fn get(&self) -> ConnectionState {
+        self.current.lock().state
+    }
+
+    pub fn set(&self, state: ConnectionState) {
+        let mut current = self.current.lock();
+        if current.state != state {
+            trace!("Connection state: {:?} -> {state:?}", current.state);
+
+            current.state = state;
+            current.version += 1;
+            self.changed.notify_all();
+        }
+    }
+}
+
+/// Watches the [`ConnectionState`] of a client, see [`Client::watch_state`](crate::Client::watch_state)
+///
+/// Each watcher keeps track of the last state it has seen,
+/// so that no change is missed between calls to [`StateWatcher::changed`].
+/// Only the latest state is kept, intermediate states may be skipped if they change quickly.
+#[derive(Debug, Clone)]
+#[allow(clippy::module_name_repetitions)]
+pub struct StateWatcher {
+    shared: Arc<SharedState>,
+    seen: u64,
+}
+
+impl StateWatcher {
+    pub(crate) fn new(shared: Arc<SharedState>) -> Self {
+        let seen = shared.current.lock().version;
+
+        Self { shared, seen }
+    }
+
+    #[must_use]
+    /// Get the current state, and mark it as seen
+    pub fn get(&mut self) -> ConnectionState {
+        let current = self.shared.current.lock();
+        self.seen = current.version;
+
+        current.state
+    }
+
+    #[must_use]
+    /// Check if the state changed since it was last seen
+    pub fn has_changed(&self) -> bool {
+        self.shared.current.lock().version != self.seen
+    }
+
+    /// Block until the state changes from the last seen state, and return the new state
+    pub fn changed(&mut self) -> ConnectionState {
+        let mut current = self.shared.current.lock();
+        while current.version == self.seen {
+            self.shared.changed.wait(&mut current);
+        }
+        self.seen = current.version;
+
+        current.state
+    }
+
+    /// Block until the state changes from the last seen state, or the timeout elapses
+    ///
+    /// Returns `None` if the timeout elapsed without a change
+    pub fn changed_timeout(&mut self, timeout: Duration) -> Option<ConnectionState> {
+        let mut current = self.shared.current.lock();
+        if current.version == self.seen {
+            self.shared.changed.wait_for(&mut current, timeout);
+        }
+
+        if current.version == self.seen {
+            return None;
+        }
+        self.seen = current.version;
+
+        Some(current.state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn watchers_see_state_changes() {
+        let shared = Arc::new(SharedState::default());
+        let mut watcher = StateWatcher::new(shared.clone());
+
+        assert_eq!(watcher.get(), ConnectionState::Idle);
+        assert!(!watcher.has_changed());
+        assert_eq!(watcher.changed_timeout(Duration::from_millis(10)), None);
+
+        // Setting the same state is not a change
+        shared.set(ConnectionState::Idle);
+        assert!(!watcher.has_changed());
+
+        let setter = shared.clone();
+        let handle = std::thread::spawn(move || setter.set(ConnectionState::Connecting));
+
+        assert_eq!(watcher.changed(), ConnectionState::Connecting);
+        handle.join().unwrap();
+
+        shared.set(ConnectionState::Handshaking);
+        shared.set(ConnectionState::Ready);
+        assert!(watcher.has_changed());
+        assert_eq!(
+            watcher.changed_timeout(Duration::from_millis(10)),
+            Some(ConnectionState::Ready)
+        );
+    }
+}
@@ -0,0 +1,162 @@
+//! A fake Discord IPC server, for testing the client without Discord running
+#![allow(dead_code)]
+
+use std::{
+    io::{Read, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::PathBuf,
+    sync::{Mutex, MutexGuard},
+    thread::{self, JoinHandle},
+};
+
+use serde_json::{json, Value};
+
+/// The client finds Discord through environment variables, so only one test can use it at a time
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+pub const CLIENT_ID: u64 = 1003450375732482138;
+
+pub struct FakeDiscord {
+    _lock: MutexGuard<'static, ()>,
+    pub handle: JoinHandle<()>,
+}
+
+/// Point the client at an empty directory, so that no Discord socket can be found
+///
+/// The environment stays locked until the returned guard is dropped.
+pub fn without_discord() -> MutexGuard<'static, ()> {
+    let lock = ENV_LOCK
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+    let dir = std::env::temp_dir().join(format!(
+        "discord-presence-empty-{}-{:?}",
+        std::process::id(),
+        thread::current().id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::env::set_var("XDG_RUNTIME_DIR", dir);
+
+    lock
+}
+
+/// Start a fake Discord server, which performs the handshake and then runs `script`
+pub fn start<F>(script: F) -> FakeDiscord
+where
+    F: FnOnce(&mut UnixStream) + Send + 'static,
+{
+    listen(move |listener| {
+        let (mut stream, _) = listener.accept().unwrap();
+
+        let (opcode, _) = read_frame(&mut stream);
+        assert_eq!(opcode, 0, "Expected a handshake");
+        write_frame(&mut stream, 1, &ready_payload());
+
+        script(&mut stream);
+    })
+}
+
+/// Start a fake Discord server, which runs `script` with the listening socket, to accept connections itself
+pub fn listen<F>(script: F) -> FakeDiscord
+where
+    F: FnOnce(&UnixListener) + Send + 'static,
+{
+    let lock = ENV_LOCK
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+    let dir = std::env::temp_dir().join(format!(
+        "discord-presence-fake-{}-{:?}",
+        std::process::id(),
+        thread::current().id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path: PathBuf = dir.join("discord-ipc-0");
+    let _ = std::fs::remove_file(&path);
+
+    let listener = UnixListener::bind(&path).unwrap();
+    std::env::set_var("XDG_RUNTIME_DIR", &dir);
+
+    let handle = thread::spawn(move || script(&listener));
+
+    FakeDiscord {
+        _lock: lock,
+        handle,
+    }
+}
+
+pub fn ready_payload() -> Value {
+    json!({
+        "cmd": "DISPATCH",
+        "evt": "READY",
+        "data": {
+            "v": 1,
+            "config": {
+                "cdn_host": "cdn.discordapp.com",
+                "api_endpoint": "//discord.com/api",
+                "environment": "production"
+            },
+            "user": {
+                "id": "1234",
+                "username": "ferris",
+                "discriminator": "0",
+                "avatar": null
+            }
+        }
+    })
+}
+
+pub fn read_frame(stream: &mut UnixStream) -> (u32, Value) {
+    try_read_frame(stream).expect("Expected a frame")
+}
+
+/// Read a frame, or return `None` once the client closed the connection
+pub fn try_read_frame(stream: &mut UnixStream) -> Option<(u32, Value)> {
+    let mut header = [0; 8];
+    stream.read_exact(&mut header).ok()?;
+
+    let opcode = u32::from_le_bytes(header[..4].try_into().unwrap());
+    let length = u32::from_le_bytes(header[4..].try_into().unwrap());
+
+    let mut payload = vec![0; length as usize];
+    stream.read_exact(&mut payload).unwrap();
+
+    Some((opcode, serde_json::from_slice(&payload).unwrap()))
+}
+
+pub fn write_frame(stream: &mut UnixStream, opcode: u32, payload: &Value) {
+    write_raw_frame(stream, opcode, &serde_json::to_vec(payload).unwrap());
+}
+
+/// Write a frame with an arbitrary payload, which does not need to be valid JSON
+pub fn write_raw_frame(stream: &mut UnixStream, opcode: u32, payload: &[u8]) {
+    let mut frame = opcode.to_le_bytes().to_vec();
+    frame.extend_from_slice(&u32::try_from(payload.len()).unwrap().to_le_bytes());
+    frame.extend_from_slice(payload);
+
+    stream.write_all(&frame).unwrap();
+}
+
+/// Read a command frame, and respond with the given data
+pub fn respond(stream: &mut UnixStream, data: &Value) -> Value {
+    let (_, request) = read_frame(stream);
+
+    write_frame(
+        stream,
+        1,
+        &json!({
+            "cmd": request["cmd"],
+            "nonce": request["nonce"],
+            "evt": null,
+            "data": data,
+        }),
+    );
+
+    request
+}
+
+/// Wait for the client to close the connection
+pub fn wait_for_close(stream: &mut UnixStream) {
+    let mut buf = [0; 1024];
+    while matches!(stream.read(&mut buf), Ok(n) if n > 0) {}
+}
@@ -0,0 +1,145 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use discord_presence::{
+    executor::HandlerExecutor, models::EventData, Client, ConnectionState, DiscordError, Event,
+};
+
+#[test]
+fn reports_last_error_when_attempts_are_exhausted() {
+    let _env = common::without_discord();
+
+    let mut drpc =
+        Client::with_error_config(1003450375732482138, Duration::from_millis(10), Some(2));
+
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let failed = Arc::new(AtomicUsize::new(0));
+
+    let attempts_inner = attempts.clone();
+    drpc.on_connection_attempt(move |ctx| {
+        let EventData::ConnectionAttempt(data) = ctx.event else {
+            unreachable!()
+        };
+        assert_eq!(data.next_delay, Some(Duration::from_millis(10)));
+        attempts_inner.fetch_add(1, Ordering::SeqCst);
+    })
+    .persist();
+
+    let failed_inner = failed.clone();
+    drpc.on_connection_failed(move |_| {
+        failed_inner.fetch_add(1, Ordering::SeqCst);
+    })
+    .persist();
+
+    // Cloned before starting, as the client thread cannot be joined while it is shared
+    let mut checker = drpc.clone();
+    let mut watcher = drpc.watch_state();
+    assert_eq!(checker.state(), ConnectionState::Idle);
+
+    drpc.start();
+
+    let result = drpc.block_on();
+
+    assert!(
+        matches!(result, Err(ref err) if err.is_not_running()),
+        "{result:?}"
+    );
+    assert!(checker.is_failed());
+    assert_eq!(checker.state(), ConnectionState::Failed);
+    assert!(watcher.has_changed());
+    assert_eq!(watcher.get(), ConnectionState::Failed);
+    assert!(matches!(
+        checker.set_activity(|a| a),
+        Err(DiscordError::ConnectionFailed)
+    ));
+
+    // Handlers run on their own threads
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    assert_eq!(failed.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn has_failed_when_connection_failed_is_handled() {
+    let _env = common::without_discord();
+
+    let mut drpc =
+        Client::with_error_config(1003450375732482138, Duration::from_millis(10), Some(0))
+            .with_handler_executor(HandlerExecutor::Inline);
+
+    let (tx, rx) = crossbeam_channel::unbounded();
+    let checker = drpc.clone();
+    drpc.on_connection_failed(move |_| {
+        tx.send((checker.is_failed(), checker.state())).unwrap();
+    })
+    .persist();
+
+    drpc.start();
+
+    let (failed, state) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert!(failed);
+    assert_eq!(state, ConnectionState::Failed);
+    assert!(!Client::is_ready());
+}
+
+#[test]
+fn waiting_for_events_stops_when_the_client_gives_up() {
+    let _env = common::without_discord();
+
+    let mut drpc =
+        Client::with_error_config(1003450375732482138, Duration::from_millis(10), Some(0));
+    drpc.start();
+
+    let result = drpc.wait_for(Event::Ready, Duration::from_secs(10), |_| true);
+    assert!(
+        matches!(result, Err(DiscordError::ConnectionFailed)),
+        "{result:?}"
+    );
+
+    // Also returns immediately once the client has already failed
+    let result = drpc.wait_for_any(
+        [Event::Ready, Event::Error],
+        Duration::from_secs(10),
+        |_, _| false,
+    );
+    assert!(
+        matches!(result, Err(DiscordError::ConnectionFailed)),
+        "{result:?}"
+    );
+}
+
+#[test]
+fn recovers_from_a_malformed_handshake_response() {
+    let server = common::listen(|listener| {
+        let (mut stream, _) = listener.accept().unwrap();
+        let (opcode, _) = common::read_frame(&mut stream);
+        assert_eq!(opcode, 0, "Expected a handshake");
+        common::write_raw_frame(&mut stream, 1, br#"{"cmd":"DISPA"#);
+        drop(stream);
+
+        let (mut stream, _) = listener.accept().unwrap();
+        let (opcode, _) = common::read_frame(&mut stream);
+        assert_eq!(opcode, 0, "Expected a handshake");
+        common::write_frame(&mut stream, 1, &common::ready_payload());
+        common::wait_for_close(&mut stream);
+    });
+
+    let mut drpc = Client::with_error_config(common::CLIENT_ID, Duration::from_millis(10), Some(3));
+    drpc.start();
+
+    drpc.wait_for(Event::Ready, Duration::from_secs(5), |_| true)
+        .unwrap();
+    assert_eq!(drpc.state(), ConnectionState::Ready);
+
+    drpc.shutdown().unwrap();
+    server.handle.join().unwrap();
+}
@@ -0,0 +1,119 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::time::Duration;
+
+use discord_presence::{
+    interceptor::{Action, Interceptor},
+    models::{payload::Payload, Command, EventData},
+    Client, DiscordError, Event,
+};
+use serde_json::{json, Value};
+
+struct Kiosk;
+
+impl Interceptor for Kiosk {
+    fn outbound(&self, payload: &mut Payload) -> Action {
+        match payload.cmd {
+            Command::SendActivityJoinInvite => Action::Drop,
+            Command::SetActivity => {
+                if let Some(args) = payload.args.as_mut() {
+                    args["activity"]["state"] = json!("intercepted");
+                }
+                Action::Pass
+            }
+            _ => Action::Pass,
+        }
+    }
+
+    fn inbound(&self, payload: &mut Payload) -> Action {
+        match (&payload.cmd, &payload.evt) {
+            (Command::CloseActivityRequest, _) => Action::Drop,
+            (Command::Dispatch, Some(Event::ActivityJoin)) => {
+                payload.data = Some(json!({ "secret": "rewritten" }));
+                Action::Pass
+            }
+            _ => Action::Pass,
+        }
+    }
+}
+
+#[test]
+fn interceptors_modify_and_drop_payloads() {
+    let (tx, rx) = crossbeam_channel::unbounded::<Value>();
+
+    let server = common::start(move |stream| {
+        // SEND_ACTIVITY_JOIN_INVITE is dropped before it is sent
+        tx.send(common::respond(stream, &json!({}))).unwrap();
+
+        common::write_frame(
+            stream,
+            1,
+            &json!({
+                "cmd": "DISPATCH",
+                "evt": "ACTIVITY_JOIN",
+                "data": { "secret": "original" },
+            }),
+        );
+
+        tx.send(common::respond(stream, &json!({}))).unwrap();
+
+        common::wait_for_close(stream);
+    });
+
+    let mut drpc = Client::new(common::CLIENT_ID)
+        .with_auto_subscribe(false)
+        .with_interceptor(Kiosk);
+
+    let (secret_tx, secret_rx) = crossbeam_channel::unbounded();
+    drpc.on_activity_join(move |ctx| {
+        // The raw payload is the one Discord sent
+        let raw_secret = ctx.raw.as_ref().map(|raw| raw["data"]["secret"].clone());
+        if let EventData::ActivityJoin(event) = ctx.event {
+            secret_tx.send((event.secret, raw_secret)).unwrap();
+        }
+    })
+    .persist();
+
+    drpc.start();
+    drpc.wait_for(Event::Ready, Duration::from_secs(5), |_| true)
+        .unwrap();
+
+    let result = drpc.send_activity_join_invite(1);
+    assert!(
+        matches!(
+            result,
+            Err(DiscordError::Intercepted {
+                command: Command::SendActivityJoinInvite
+            })
+        ),
+        "{result:?}"
+    );
+
+    drpc.set_activity(|a| a.state("original")).unwrap();
+    let request = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(request["cmd"], "SET_ACTIVITY");
+    assert_eq!(request["args"]["activity"]["state"], "intercepted");
+
+    let (secret, raw_secret) = secret_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(secret.as_deref(), Some("rewritten"));
+    assert_eq!(raw_secret, Some(json!("original")));
+
+    // The response is dropped, so the command fails instead of waiting forever
+    let result = drpc.close_activity_request(1);
+    assert!(
+        matches!(
+            result,
+            Err(DiscordError::Intercepted {
+                command: Command::CloseActivityRequest
+            })
+        ),
+        "{result:?}"
+    );
+    let request = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(request["cmd"], "CLOSE_ACTIVITY_REQUEST");
+
+    drpc.shutdown().unwrap();
+    server.handle.join().unwrap();
+}
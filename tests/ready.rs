@@ -0,0 +1,205 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::time::Duration;
+
+use discord_presence::{
+    executor::HandlerExecutor,
+    models::{event_types, EventData},
+    Client, ConnectionState, DiscordError, Event,
+};
+use serde_json::json;
+
+#[test]
+fn caches_ready_data() {
+    let server = common::start(common::wait_for_close);
+
+    let mut drpc = Client::new(common::CLIENT_ID);
+    assert!(drpc.current_user().is_none());
+
+    drpc.start();
+    drpc.block_until_event(Event::Connected).unwrap();
+
+    let user = drpc.current_user().unwrap();
+    assert_eq!(user.username.as_deref(), Some("ferris"));
+
+    let config = drpc.server_config().unwrap();
+    assert_eq!(config.cdn_host.as_deref(), Some("cdn.discordapp.com"));
+
+    assert_eq!(drpc.state(), ConnectionState::Ready);
+
+    drpc.shutdown().unwrap();
+    server.handle.join().unwrap();
+}
+
+#[test]
+fn clears_ready_data_on_disconnect() {
+    let (step_tx, step_rx) = crossbeam_channel::unbounded();
+
+    let server = common::listen(move |listener| {
+        let (mut stream, _) = listener.accept().unwrap();
+        common::read_frame(&mut stream);
+        common::write_frame(&mut stream, 1, &common::ready_payload());
+
+        step_rx.recv().unwrap();
+        drop(stream);
+
+        let (mut stream, _) = listener.accept().unwrap();
+        common::read_frame(&mut stream);
+        step_rx.recv().unwrap();
+
+        let mut ready = common::ready_payload();
+        ready["data"]["user"]["username"] = json!("crab");
+        common::write_frame(&mut stream, 1, &ready);
+        common::wait_for_close(&mut stream);
+    });
+
+    let mut drpc = Client::new(common::CLIENT_ID).with_sticky_events(true);
+
+    let (disconnected_tx, disconnected_rx) = crossbeam_channel::unbounded();
+    drpc.on_disconnected(move |_ctx| disconnected_tx.send(()).unwrap())
+        .persist();
+
+    drpc.start();
+    drpc.block_until_event(Event::Connected).unwrap();
+    assert_eq!(
+        drpc.current_user()
+            .and_then(|user| user.username)
+            .as_deref(),
+        Some("ferris")
+    );
+
+    step_tx.send(()).unwrap();
+    disconnected_rx
+        .recv_timeout(Duration::from_secs(5))
+        .unwrap();
+
+    assert!(drpc.ready_data().is_none());
+    assert!(drpc.current_user().is_none());
+    assert!(drpc.server_config().is_none());
+    // Not replayed while disconnected
+    let result = drpc.wait_for(Event::Ready, Duration::from_millis(100), |_| true);
+    assert!(
+        matches!(result, Err(DiscordError::EventTimeout { .. })),
+        "{result:?}"
+    );
+
+    step_tx.send(()).unwrap();
+    drpc.block_until_event(Event::Connected).unwrap();
+    assert_eq!(
+        drpc.current_user()
+            .and_then(|user| user.username)
+            .as_deref(),
+        Some("crab")
+    );
+
+    // Replayed with the data of the new connection
+    let ready = drpc.block_until_event(Event::Ready).unwrap();
+    let EventData::Ready(ready) = ready.event else {
+        unreachable!()
+    };
+    assert_eq!(
+        ready.user.and_then(|user| user.username).as_deref(),
+        Some("crab")
+    );
+
+    drpc.shutdown().unwrap();
+    server.handle.join().unwrap();
+}
+
+#[test]
+fn replays_ready_to_late_handlers() {
+    let server = common::start(common::wait_for_close);
+
+    let mut drpc = Client::new(common::CLIENT_ID).with_sticky_events(true);
+    drpc.start();
+    drpc.block_until_event(Event::Connected).unwrap();
+
+    // Both events already fired, so these only return because they are sticky
+    let ready = drpc.block_until_event(Event::Ready).unwrap();
+    assert!(matches!(ready.event, EventData::Ready(_)));
+    // Replays keep the metadata of the original event
+    assert_eq!(ready.raw, Some(common::ready_payload()));
+    assert_eq!(ready.generation, 1);
+    drpc.block_until_event(Event::Connected).unwrap();
+
+    let (tx, rx) = crossbeam_channel::unbounded();
+    let _ready = drpc.on_ready(move |_ctx| tx.send(()).unwrap());
+    rx.recv_timeout(Duration::from_secs(5)).unwrap();
+
+    drpc.shutdown().unwrap();
+    server.handle.join().unwrap();
+}
+
+#[test]
+fn typed_handlers_receive_event_data() {
+    let server = common::start(common::wait_for_close);
+
+    let mut drpc = Client::new(common::CLIENT_ID);
+
+    let (tx, rx) = crossbeam_channel::unbounded();
+    let _ready = drpc.on::<event_types::Ready, _>(move |ready, _ctx| tx.send(ready).unwrap());
+
+    drpc.start();
+
+    let ready = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(
+        ready.user.and_then(|user| user.username).as_deref(),
+        Some("ferris")
+    );
+
+    drpc.shutdown().unwrap();
+    server.handle.join().unwrap();
+}
+
+#[test]
+fn ordered_executor_keeps_event_order() {
+    let server = common::start(common::wait_for_close);
+
+    let mut drpc = Client::new(common::CLIENT_ID).with_handler_executor(HandlerExecutor::Ordered);
+
+    let (tx, rx) = crossbeam_channel::unbounded();
+    let ready_tx = tx.clone();
+    let _ready = drpc.on_ready(move |_ctx| {
+        // Slow handlers don't let later events overtake them
+        std::thread::sleep(Duration::from_millis(100));
+        ready_tx.send(Event::Ready).unwrap();
+    });
+    let _connected = drpc.on_connected(move |_ctx| tx.send(Event::Connected).unwrap());
+
+    drpc.start();
+
+    let first = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    let second = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!((first, second), (Event::Ready, Event::Connected));
+
+    drpc.shutdown().unwrap();
+    server.handle.join().unwrap();
+}
+
+#[test]
+fn waits_for_matching_events() {
+    let server = common::start(common::wait_for_close);
+
+    let mut drpc = Client::new(common::CLIENT_ID);
+    drpc.start();
+
+    let (event, _ctx) = drpc
+        .wait_for_any(
+            [Event::Ready, Event::Error],
+            Duration::from_secs(5),
+            |_, _| true,
+        )
+        .unwrap();
+    assert_eq!(event, Event::Ready);
+
+    let result = drpc.wait_for(Event::Connected, Duration::from_millis(100), |_| false);
+    assert!(
+        matches!(result, Err(DiscordError::EventTimeout { ref events, .. }) if events == &[Event::Connected]),
+        "{result:?}"
+    );
+
+    drpc.shutdown().unwrap();
+    server.handle.join().unwrap();
+}
@@ -0,0 +1,164 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::time::Duration;
+
+use crossbeam_channel::Receiver;
+use discord_presence::{
+    models::EventData,
+    session::{EntryKind, Replay},
+    Client,
+};
+use serde_json::json;
+
+/// Listen for ACTIVITY_JOIN, sending the secret of each event
+fn on_join(drpc: &Client) -> Receiver<Option<String>> {
+    let (tx, rx) = crossbeam_channel::unbounded();
+
+    drpc.on_activity_join(move |ctx| {
+        if let EventData::ActivityJoin(event) = ctx.event {
+            tx.send(event.secret).unwrap();
+        }
+    })
+    .persist();
+
+    rx
+}
+
+#[test]
+fn replays_recorded_sessions() {
+    let path = std::env::temp_dir().join(format!(
+        "discord-presence-recording-{}.jsonl",
+        std::process::id()
+    ));
+
+    let server = common::start(|stream| {
+        // Automatic SUBSCRIBE for the ACTIVITY_JOIN handler
+        common::respond(stream, &json!({ "evt": "ACTIVITY_JOIN" }));
+
+        common::write_frame(
+            stream,
+            1,
+            &json!({
+                "cmd": "DISPATCH",
+                "evt": "ACTIVITY_JOIN",
+                "data": { "secret": "recorded" },
+            }),
+        );
+
+        common::respond(stream, &json!({ "state": "from discord" }));
+
+        common::wait_for_close(stream);
+    });
+
+    let mut drpc = Client::new(common::CLIENT_ID)
+        .with_session_recording(&path)
+        .unwrap();
+    let secrets = on_join(&drpc);
+
+    drpc.start();
+
+    let secret = secrets.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(secret.as_deref(), Some("recorded"));
+
+    let activity = drpc.set_activity(|a| a.state("playing")).unwrap();
+    assert_eq!(
+        activity.data.unwrap().state.as_deref(),
+        Some("from discord")
+    );
+
+    drpc.shutdown().unwrap();
+    server.handle.join().unwrap();
+
+    // Replay the recording, without Discord
+    let replay = Replay::from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(replay.remaining(), 7);
+
+    let mut drpc = Client::new(common::CLIENT_ID).with_replay(replay.clone());
+    let secrets = on_join(&drpc);
+
+    drpc.start();
+
+    let secret = secrets.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(secret.as_deref(), Some("recorded"));
+
+    // Answered with the recorded response, matched to the new nonce
+    let activity = drpc.set_activity(|a| a.state("playing")).unwrap();
+    assert_eq!(
+        activity.data.unwrap().state.as_deref(),
+        Some("from discord")
+    );
+
+    assert!(replay.wait_until_finished(Duration::from_secs(5)));
+
+    // Commands that are not part of the recording fail, instead of waiting forever
+    assert!(drpc.clear_activity().is_err());
+
+    drpc.shutdown().unwrap();
+}
+
+#[test]
+fn records_every_frame() {
+    let path = std::env::temp_dir().join(format!(
+        "discord-presence-frames-{}.jsonl",
+        std::process::id()
+    ));
+
+    let server = common::start(|stream| {
+        common::respond(stream, &json!({}));
+        common::wait_for_close(stream);
+    });
+
+    let mut drpc = Client::new(common::CLIENT_ID)
+        .with_session_recording(&path)
+        .unwrap();
+    drpc.start();
+    drpc.wait_for(
+        discord_presence::Event::Ready,
+        Duration::from_secs(5),
+        |_| true,
+    )
+    .unwrap();
+    drpc.clear_activity().unwrap();
+    drpc.shutdown().unwrap();
+    server.handle.join().unwrap();
+
+    let replay = Replay::from_file(&path).unwrap();
+    let recording = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let entries: Vec<serde_json::Value> = recording
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    assert_eq!(replay.remaining(), entries.len());
+    assert_eq!(entries.len(), 4);
+
+    assert_eq!(entries[0]["kind"], "sent");
+    assert_eq!(entries[0]["opcode"], "HANDSHAKE");
+    assert_eq!(entries[1]["kind"], "received");
+    assert_eq!(entries[1]["payload"]["evt"], "READY");
+    assert_eq!(entries[2]["payload"]["cmd"], "SET_ACTIVITY");
+    assert_eq!(
+        entries[3]["payload"]["nonce"],
+        entries[2]["payload"]["nonce"]
+    );
+
+    let kinds: Vec<EntryKind> = entries
+        .iter()
+        .map(|entry| serde_json::from_value(entry["kind"].clone()).unwrap())
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            EntryKind::Sent,
+            EntryKind::Received,
+            EntryKind::Sent,
+            EntryKind::Received
+        ]
+    );
+}
@@ -0,0 +1,120 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::time::Duration;
+
+use crossbeam_channel::Sender;
+use discord_presence::{models::SubscriptionArgs, Client, Event};
+use serde_json::{json, Value};
+
+/// Start a fake Discord server that answers every command, sending each request to `tx`
+fn answer_everything(tx: Sender<Value>) -> common::FakeDiscord {
+    common::start(move |stream| {
+        // Answer every command until the client disconnects
+        while let Some((_, request)) = common::try_read_frame(stream) {
+            common::write_frame(
+                stream,
+                1,
+                &json!({
+                    "cmd": request["cmd"],
+                    "nonce": request["nonce"],
+                    "evt": request["evt"],
+                    "data": { "evt": request["evt"] },
+                }),
+            );
+            tx.send(request).unwrap();
+        }
+    })
+}
+
+#[test]
+fn subscribes_while_handlers_are_registered() {
+    let (tx, rx) = crossbeam_channel::unbounded::<Value>();
+    let server = answer_everything(tx);
+
+    let mut drpc = Client::new(common::CLIENT_ID);
+    let join1 = drpc.on_activity_join(|_| {});
+    let join2 = drpc.on_activity_join(|_| {});
+
+    drpc.start();
+    drpc.wait_for(Event::Ready, Duration::from_secs(5), |_| true)
+        .unwrap();
+
+    let subscribe = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(subscribe["cmd"], "SUBSCRIBE");
+    assert_eq!(subscribe["evt"], "ACTIVITY_JOIN");
+
+    // Only the last handler unsubscribes, so the next frame is the command sent after dropping the first one
+    drop(join1);
+    drpc.clear_activity().unwrap();
+
+    drop(join2);
+    let frames: Vec<Value> = (0..2)
+        .map(|_| rx.recv_timeout(Duration::from_secs(5)).unwrap())
+        .collect();
+    let commands: Vec<&Value> = frames.iter().map(|frame| &frame["cmd"]).collect();
+    assert_eq!(commands, ["SET_ACTIVITY", "UNSUBSCRIBE"]);
+    assert_eq!(frames[1]["evt"], "ACTIVITY_JOIN");
+
+    drpc.shutdown().unwrap();
+    server.handle.join().unwrap();
+
+    // Nothing else was sent, such as a second UNSUBSCRIBE
+    assert!(rx.try_recv().is_err());
+}
+
+#[test]
+fn counts_manual_subscriptions_with_handlers() {
+    let (tx, rx) = crossbeam_channel::unbounded::<Value>();
+    let server = answer_everything(tx);
+
+    let mut drpc = Client::new(common::CLIENT_ID);
+    let join = drpc.on_activity_join(|_| {});
+
+    drpc.start();
+    drpc.wait_for(Event::Ready, Duration::from_secs(5), |_| true)
+        .unwrap();
+
+    let subscribe = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(subscribe["cmd"], "SUBSCRIBE");
+    assert_eq!(subscribe["evt"], "ACTIVITY_JOIN");
+
+    // Already subscribed for the handler, so nothing is sent
+    let subscription = drpc
+        .subscribe(Event::ActivityJoin, SubscriptionArgs::default())
+        .unwrap();
+    assert_eq!(
+        subscription.data.and_then(|data| data.evt).as_deref(),
+        Some("ACTIVITY_JOIN")
+    );
+
+    // The manual subscription is kept after the last handler is dropped
+    drop(join);
+    drpc.clear_activity().unwrap();
+    let request = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(request["cmd"], "SET_ACTIVITY");
+
+    drpc.unsubscribe(Event::ActivityJoin, SubscriptionArgs::default())
+        .unwrap();
+    let request = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(request["cmd"], "UNSUBSCRIBE");
+    assert_eq!(request["evt"], "ACTIVITY_JOIN");
+
+    // A handler registered after a manual subscription does not subscribe again, or unsubscribe when dropped
+    drpc.subscribe(Event::ActivitySpectate, SubscriptionArgs::default())
+        .unwrap();
+    let request = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(request["cmd"], "SUBSCRIBE");
+    assert_eq!(request["evt"], "ACTIVITY_SPECTATE");
+
+    drop(drpc.on_activity_spectate(|_| {}));
+    drpc.clear_activity().unwrap();
+    let request = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(request["cmd"], "SET_ACTIVITY");
+
+    drpc.shutdown().unwrap();
+    server.handle.join().unwrap();
+
+    assert!(rx.try_recv().is_err());
+}